@@ -0,0 +1,262 @@
+use clap::Parser;
+use hyperbol::aggregate::Aggregated;
+use hyperbol::embed::{hyperbolic_embed, EmbedOptions};
+use hyperbol::io::{binary, dot, newick, read_file};
+use hyperbol::{
+    centrality, community, hyperbolic_project_with, svg, DFOut, Direction, Graph, GraphStats,
+    Layout, ProjectOptions, DAGID, FP,
+};
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Lays out a graph file in the Poincare disc.
+#[derive(Parser)]
+struct Args {
+    /// Graph to read, by extension: GraphML, GEXF, DOT, Newick, node-link or tree JSON (with the
+    /// `serde` feature), `.hypb` binary graphs, or otherwise an edge list.
+    #[clap(long)]
+    input: String,
+
+    /// Index of the node placed at the center.
+    #[clap(long, default_value_t = 0)]
+    focus: usize,
+
+    /// Focus on the most central node by betweenness instead of `--focus`.
+    #[clap(long)]
+    auto_focus: bool,
+
+    /// Use the `length` node attribute, e.g. Newick branch lengths, as edge lengths.
+    #[clap(long)]
+    branch_lengths: bool,
+
+    /// Use this numeric edge attribute, e.g. `weight`, as edge lengths.
+    #[clap(long, conflicts_with = "branch-lengths")]
+    weights: Option<String>,
+
+    /// Which edges to follow from the focus: `out` to descendants, `in` to ancestors, or `both`.
+    #[clap(long, default_value = "both", parse(try_from_str = parse_direction))]
+    direction: Direction,
+
+    /// Lay out descendants of the focus in the upper half of the disc and ancestors in the lower.
+    #[clap(long)]
+    split: bool,
+
+    /// Simplify the graph before laying it out: `reduction` drops edges implied by longer paths,
+    /// `dominators` keeps only the edge from each node's immediate dominator from the focus, and
+    /// `communities` lays out a tree of nested communities, focused on its root.
+    #[clap(long)]
+    simplify: Option<String>,
+
+    /// Highlight a shortest path from the focus to this node, following edges as in `--direction`.
+    /// Only drawn in SVG output.
+    #[clap(long)]
+    path_to: Option<usize>,
+
+    /// Only lay out nodes whose attribute has this value, as `NAME=VALUE`, e.g. `year=2020`.
+    /// The focus must match.
+    #[clap(long)]
+    keep: Option<String>,
+
+    /// Compare with a newer version of the input, laying out both together with added nodes in
+    /// green and removed nodes in red. Nodes are matched by name.
+    #[clap(long)]
+    diff: Option<String>,
+
+    /// Show the leaf children of each node beyond the first N as a single node. Only drawn in SVG
+    /// output.
+    #[clap(long)]
+    collapse_leaves: Option<usize>,
+
+    /// Place nodes by optimizing hyperbolic distances to match distances in the graph, instead of
+    /// projecting a spanning tree. Ignores edge lengths and `--split`.
+    #[clap(long)]
+    embed: bool,
+
+    /// Print statistics of the graph, with depths from the focus, instead of laying it out.
+    #[clap(long)]
+    stats: bool,
+
+    /// Where to write the layout, as SVG, DOT or a `.hypb` binary graph by extension. Writes SVG
+    /// to stdout if omitted.
+    #[clap(long)]
+    output: Option<String>,
+}
+
+fn parse_direction(s: &str) -> Result<Direction, String> {
+    match s {
+        "out" => Ok(Direction::Outgoing),
+        "in" => Ok(Direction::Incoming),
+        "both" => Ok(Direction::Both),
+        _ => Err(format!("unknown direction {s:?}, expected out, in or both")),
+    }
+}
+
+/// Coordinates, traversal info, the path to highlight and labels of collapsed leaves.
+type Projection = (Vec<[FP; 2]>, Vec<DFOut>, Vec<DAGID>, Vec<String>);
+
+/// Lays out `g`, collapsing leaves if asked, and finds the path to highlight.
+fn project(
+    g: &impl Graph,
+    args: &Args,
+    opts: &ProjectOptions,
+) -> Result<Projection, Box<dyn Error>> {
+    let Some(threshold) = args.collapse_leaves else {
+        return layout(g, args, opts);
+    };
+    let agg = Aggregated::new(g, threshold);
+    let mut projection = layout(&agg, args, opts)?;
+    projection.3 = (0..agg.groups().len())
+        .map(|i| format!("{} more", agg.count(agg.meta_node(i))))
+        .collect();
+    Ok(projection)
+}
+
+fn layout(
+    g: &impl Graph,
+    args: &Args,
+    opts: &ProjectOptions,
+) -> Result<Projection, Box<dyn Error>> {
+    let (coords, info): (_, Vec<_>) = if args.embed {
+        let (coords, info) = hyperbolic_embed(g, args.focus, &EmbedOptions::default());
+        (coords, info.collect())
+    } else {
+        let (coords, info) = hyperbolic_project_with(g, args.focus, opts);
+        (coords, info.collect())
+    };
+    let path = match args.path_to {
+        Some(to) if to < g.num_nodes() => g
+            .shortest_path(args.focus, to, args.direction)
+            .ok_or_else(|| format!("no path from {} to {to}", args.focus))?,
+        Some(to) => return Err(format!("path target {to} out of range").into()),
+        None => vec![],
+    };
+    Ok((coords, info, path, vec![]))
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = Args::parse();
+    let mut graph = read_file(&args.input)?;
+    let mut colors = None;
+    if let Some(newer) = &args.diff {
+        let diff = graph.diff(&read_file(newer)?)?;
+        colors = Some(diff.node_colors());
+        graph = diff.union;
+    }
+    if args.auto_focus {
+        args.focus = centrality::suggest_focus(&graph).ok_or("graph has no nodes")?;
+    }
+    if args.focus >= graph.num_nodes() {
+        return Err(format!("focus {} out of range", args.focus).into());
+    }
+    match args.simplify.as_deref() {
+        None => {}
+        Some("reduction") => {
+            graph = graph
+                .transitive_reduction()
+                .ok_or("transitive reduction requires an acyclic graph")?
+        }
+        Some("dominators") => graph = graph.dominator_tree(args.focus),
+        Some("communities") => {
+            let tree = community::community_tree(&graph, 1.);
+            // Leaves keep the DAGIDs, and so the attributes, of the graph's nodes.
+            let mut named = tree.map(|v| match v {
+                Some(v) => graph.get(*v).clone(),
+                None => String::new(),
+            });
+            for i in graph.num_nodes()..named.num_nodes() {
+                *named.get_mut(i) = format!("community {i}");
+            }
+            let mut attrs = graph.node_attrs().clone();
+            attrs.resize(named.num_nodes());
+            *named.node_attrs_mut() = attrs;
+            args.focus = named.num_nodes() - 1;
+            graph = named
+        }
+        Some(s) => {
+            return Err(format!(
+                "unknown simplification {s:?}, expected reduction, dominators or communities"
+            )
+            .into())
+        }
+    }
+    if args.stats {
+        print!("{}", GraphStats::new(&graph, args.focus));
+        return Ok(());
+    }
+    let lengths = match &args.weights {
+        Some(name) => Some(
+            graph
+                .edge_lengths(name)
+                .ok_or_else(|| format!("no numeric edge attribute {name:?}"))?,
+        ),
+        None => args.branch_lengths.then(|| {
+            let lengths = graph.node_attrs().floats("length");
+            let lengths = lengths.unwrap_or_else(|| vec![None; graph.num_nodes()]);
+            newick::edge_lengths_from_nodes(&graph, &lengths)
+        }),
+    };
+    let opts = ProjectOptions {
+        edge_lengths: lengths.as_deref(),
+        direction: args.direction,
+        split: args.split,
+    };
+    let (mut coords, mut info, path, collapsed) = match &args.keep {
+        Some(keep) => {
+            let (name, value) = keep.split_once('=').ok_or("expected --keep NAME=VALUE")?;
+            let attrs = graph.node_attrs();
+            let view = graph
+                .filter_nodes(|i, _| attrs.get(name, i).is_some_and(|v| v.to_string() == value));
+            if !view.contains_node(args.focus) {
+                return Err(format!("focus {} does not have {keep}", args.focus).into());
+            }
+            project(&view, &args, &opts)?
+        }
+        None => project(&graph, &args, &opts)?,
+    };
+
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    let ext = args.output.as_ref().and_then(|p| Path::new(p).extension());
+    let ext = ext.and_then(|e| e.to_str());
+    if matches!(ext, Some("dot" | "gv" | "hypb")) {
+        // Collapsed leaves have no node to be written as.
+        coords.truncate(graph.num_nodes());
+        info.retain(|v| v.dagid < graph.num_nodes());
+    }
+    match ext {
+        Some("dot" | "gv") => dot::write(&mut out, &graph, Some(&coords))?,
+        Some("hypb") => {
+            let layout = Layout {
+                focus: args.focus,
+                coords,
+                info,
+            };
+            binary::write(&mut out, &graph, Some(&layout))?
+        }
+        _ => {
+            let mut opts = svg::RenderOptions {
+                highlight: &path,
+                ..svg::RenderOptions::from_attrs(graph.node_attrs())
+            };
+            if let Some(colors) = &colors {
+                opts.colors = Some(colors);
+            }
+            let labels: Vec<_>;
+            if !collapsed.is_empty() {
+                let names = opts.labels.unwrap_or_default();
+                labels = (0..graph.num_nodes())
+                    .map(|i| names.get(i).cloned().flatten())
+                    .chain(collapsed.into_iter().map(Some))
+                    .collect();
+                opts.labels = Some(&labels);
+            }
+            let svg = svg::render_with(&coords, info, 800., &opts);
+            out.write_all(svg.as_bytes())?
+        }
+    }
+    Ok(())
+}
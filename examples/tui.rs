@@ -7,7 +7,9 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use hyperbol::{hyperbolic_project, PoincarePoint, DAG, DAGID};
+use hyperbol::io::read_file;
+use hyperbol::lazy::{project_lazy, Expand, LazyDag};
+use hyperbol::{hyperbolic_project, PoincarePoint, DAGID};
 use std::collections::HashMap;
 use std::fs::{canonicalize, metadata};
 use std::path::{absolute, Path, MAIN_SEPARATOR};
@@ -21,23 +23,60 @@ use tui::{
     widgets::{Block, Borders, Paragraph, Wrap},
     Frame, Terminal,
 };
-use walkdir::WalkDir;
 
 #[derive(Parser)]
 struct Args {
     /// The root directory to render from.
-    #[clap(long)]
+    #[clap(long, default_value = ".")]
     root: String,
 
+    /// A graph file (GraphML, GEXF, DOT or an edge list) to render instead of a directory.
+    #[clap(long)]
+    graph: Option<String>,
+
+    /// Depth of directories listed up front, deeper ones are listed as they are panned to.
     #[clap(long, default_value_t = 3)]
-    depth: u32,
+    depth: usize,
+
+    /// Hyperbolic distance from the center of the view within which directories are listed.
+    #[clap(long, default_value_t = 3.)]
+    expand_radius: f64,
 }
 
-/// Represents the state of the TUI
+/// Lists directories as they come into view.
 #[derive(Debug)]
+struct FsSource;
+
+impl Expand for FsSource {
+    type Node = String;
+    fn children(&mut self, path: &String) -> Vec<String> {
+        // Symlinks aren't followed, so cyclic links can't be expanded forever.
+        if !std::fs::symlink_metadata(path).is_ok_and(|md| md.is_dir()) {
+            return vec![];
+        }
+        let Ok(entries) = std::fs::read_dir(path) else {
+            return vec![]
+        };
+        let mut children: Vec<_> = entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.path().to_str().map(String::from))
+            .collect();
+        children.sort();
+        children
+    }
+}
+
+/// Represents the state of the TUI
 struct App {
     shift: [f64; 2],
 
+    /// The directory tree being browsed, or `None` if showing a graph file.
+    fs: Option<LazyDag<FsSource>>,
+    /// Depth of directories listed up front when changing root.
+    depth: usize,
+    /// Hyperbolic distance from the center of the view within which directories are listed.
+    expand_radius: f64,
+
     id_to_path: HashMap<DAGID, String>,
 
     path_positions: Vec<[f64; 2]>,
@@ -63,39 +102,74 @@ fn is_dir(s: &str) -> bool {
     Path::new(s).is_dir()
 }
 
-impl App {
-    pub fn new(root: &str, max_depth: usize) -> Self {
-        let mut dag = DAG::new();
-        let iter = WalkDir::new(root).min_depth(0).max_depth(max_depth);
-        let iter = iter.into_iter().filter_map(|v| v.ok());
-        let mut path_to_id = HashMap::new();
-        let mut id_to_path = HashMap::new();
-        for entry in iter {
-            let path = entry.path().to_str().unwrap().to_string();
-            let insert_id = dag.insert(path.clone());
-            if entry.depth() > 0 {
-                let parent_path = entry.path().parent().unwrap().to_str().unwrap().to_string();
-                let parent_id = path_to_id[&parent_path];
-                dag.insert_edge(parent_id, insert_id);
-            }
-            assert_eq!(id_to_path.insert(insert_id, path.clone()), None);
-            assert_eq!(path_to_id.insert(path, insert_id), None);
-        }
-
-        let (path_positions, _) = hyperbolic_project(&dag, 0);
+/// Maximum number of times the view is re-projected after listing directories for one pan.
+const MAX_EXPAND_ROUNDS: usize = 4;
 
+impl App {
+    pub fn new(root: &str, depth: usize, expand_radius: f64) -> Self {
         let mut buffer = canonical(root).unwrap();
 
         if !buffer.ends_with(MAIN_SEPARATOR) && is_dir(&buffer) {
             buffer.push(MAIN_SEPARATOR)
         }
 
-        Self {
+        let mut app = Self {
+            shift: [0.; 2],
+            fs: None,
+            depth,
+            expand_radius,
+            id_to_path: HashMap::new(),
+            path_positions: vec![],
+            buffer,
+        };
+        app.browse(root.to_string());
+        app
+    }
+
+    pub fn from_graph(path: &str) -> Result<Self, hyperbol::io::Error> {
+        let graph = read_file(path)?;
+        let labels = graph.node_attrs().strs("label");
+        let id_to_path = (0..graph.num_nodes())
+            .map(|i| {
+                let label = labels.and_then(|l| l[i].clone());
+                (i, label.unwrap_or_else(|| graph.get(i).clone()))
+            })
+            .collect();
+        let (path_positions, _) = hyperbolic_project(&graph, 0);
+        Ok(Self {
             shift: [0.; 2],
+            fs: None,
+            depth: 0,
+            expand_radius: 0.,
             id_to_path,
             path_positions,
-            buffer,
-        }
+            buffer: path.to_string(),
+        })
+    }
+
+    /// Starts browsing from `root`, listing `self.depth` levels eagerly.
+    fn browse(&mut self, root: String) {
+        let mut fs = LazyDag::new(FsSource, root);
+        fs.expand_depth(0, self.depth);
+        self.fs = Some(fs);
+        self.shift = [0.; 2];
+        self.reproject();
+    }
+
+    /// Lists directories near the center of the view, and lays out the tree again.
+    pub fn reproject(&mut self) {
+        let Some(fs) = &mut self.fs else {
+            return
+        };
+        // The point shown at the center of the view is the one which is shifted onto the origin.
+        let center = PoincarePoint::exp(&self.shift).neg();
+        let (path_positions, _) =
+            project_lazy(fs, 0, &center, self.expand_radius, MAX_EXPAND_ROUNDS);
+        let dag = fs.dag();
+        self.id_to_path = (0..dag.num_nodes())
+            .map(|i| (i, dag.get(i).clone()))
+            .collect();
+        self.path_positions = path_positions;
     }
 
     pub fn reset_root(&mut self) -> bool {
@@ -106,40 +180,21 @@ impl App {
         let Ok(root) = absolute(&self.buffer) else {
             return false
         };
-        let mut dag = DAG::new();
-        let iter = WalkDir::new(root).min_depth(0).max_depth(3);
-        let iter = iter.into_iter().filter_map(|v| v.ok());
-        let mut path_to_id = HashMap::new();
-        let mut id_to_path = HashMap::new();
-        for entry in iter {
-            let path = entry.path().to_str().unwrap().to_string();
-            let insert_id = dag.insert(path.clone());
-            if entry.depth() > 0 {
-                let parent_path = entry.path().parent().unwrap().to_str().unwrap().to_string();
-                let Some(parent_id) = path_to_id.get(&parent_path) else {
-                  continue
-                };
-                dag.insert_edge(*parent_id, insert_id);
-            }
-            assert_eq!(id_to_path.insert(insert_id, path.clone()), None);
-            assert_eq!(path_to_id.insert(path, insert_id), None);
-        }
-
-        if id_to_path.is_empty() {
-            return false;
-        }
-
-        let (path_positions, _) = hyperbolic_project(&dag, 0);
-
-        self.id_to_path = id_to_path;
-        self.path_positions = path_positions;
-        self.shift = [0.; 2];
+        let Some(root) = root.to_str().filter(|r| metadata(r).is_ok()) else {
+            return false
+        };
+        self.browse(root.to_string());
         true
     }
 }
 
 fn main() -> Result<(), io::Error> {
     let args = Args::parse();
+    let app = match &args.graph {
+        Some(graph) => App::from_graph(graph)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?,
+        None => App::new(&args.root, args.depth, args.expand_radius),
+    };
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -147,7 +202,7 @@ fn main() -> Result<(), io::Error> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    run_app(&mut terminal, App::new(&args.root, 3))?;
+    run_app(&mut terminal, app)?;
 
     // restore terminal
     disable_raw_mode()?;
@@ -171,15 +226,19 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
                     KeyCode::Esc => return Ok(()),
                     KeyCode::Down => {
                         app.shift[1] -= eps;
+                        app.reproject();
                     }
                     KeyCode::Up => {
                         app.shift[1] += eps;
+                        app.reproject();
                     }
                     KeyCode::Right => {
                         app.shift[0] += eps;
+                        app.reproject();
                     }
                     KeyCode::Left => {
                         app.shift[0] -= eps;
+                        app.reproject();
                     }
                     KeyCode::Backspace | KeyCode::Delete => {
                         app.buffer.pop();
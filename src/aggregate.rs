@@ -0,0 +1,213 @@
+//! Collapsing the many leaf children of a node into a single meta-node, so that e.g. a directory
+//! with thousands of files doesn't crowd out the rest of a layout.
+
+use super::dag::{EdgeID, DAGID};
+use super::graph::Graph;
+use std::collections::HashMap;
+
+/// Leaf children of a node which are shown as one meta-node while collapsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeafGroup {
+    pub parent: DAGID,
+    pub leaves: Vec<DAGID>,
+    pub collapsed: bool,
+}
+
+/// A graph with groups of sibling leaves replaced by meta-nodes, which can be projected like the
+/// graph itself so that each collapsed group takes up a single wedge.
+///
+/// Meta-nodes are numbered after the nodes of the graph, which keep their DAGIDs.
+pub struct Aggregated<'a, G> {
+    graph: &'a G,
+    groups: Vec<LeafGroup>,
+    /// Index of the group of each parent with one.
+    group_of: HashMap<DAGID, usize>,
+    /// Whether each node of the graph is in a collapsed group.
+    hidden: Vec<bool>,
+}
+
+impl<'a, G: Graph> Aggregated<'a, G> {
+    /// Collapses the leaf children of each node beyond the first `threshold` into a meta-node.
+    /// Leaves are nodes with no outgoing edges and a single incoming edge.
+    pub fn new(graph: &'a G, threshold: usize) -> Self {
+        let is_leaf = |v: DAGID| {
+            graph.node(v).is_some()
+                && graph.neighbors(v).next().is_none()
+                && graph.incoming(v).count() == 1
+        };
+        let mut out = Self {
+            graph,
+            groups: vec![],
+            group_of: HashMap::new(),
+            hidden: vec![false; graph.num_nodes()],
+        };
+        for parent in 0..graph.num_nodes() {
+            let leaves: Vec<_> = graph.neighbors(parent).filter(|&v| is_leaf(v)).collect();
+            if leaves.len() > threshold {
+                out.group_of.insert(parent, out.groups.len());
+                out.groups.push(LeafGroup {
+                    parent,
+                    leaves: leaves[threshold..].to_vec(),
+                    collapsed: false,
+                });
+                out.set_collapsed(out.groups.len() - 1, true);
+            }
+        }
+        out
+    }
+    pub fn graph(&self) -> &'a G {
+        self.graph
+    }
+    pub fn groups(&self) -> &[LeafGroup] {
+        &self.groups
+    }
+    /// DAGID of the meta-node of the `i`th group.
+    pub fn meta_node(&self, i: usize) -> DAGID {
+        self.graph.num_nodes() + i
+    }
+    /// The group a meta-node stands for, or `None` for nodes of the graph.
+    pub fn group(&self, id: DAGID) -> Option<&LeafGroup> {
+        self.groups.get(id.checked_sub(self.graph.num_nodes())?)
+    }
+    /// Number of the graph's nodes shown as `id`: the size of the group for a collapsed
+    /// meta-node, 0 for nodes which aren't shown, and 1 otherwise.
+    pub fn count(&self, id: DAGID) -> usize {
+        match self.group(id) {
+            Some(g) if g.collapsed => g.leaves.len(),
+            Some(_) => 0,
+            None => (self.hidden.get(id) == Some(&false)) as usize,
+        }
+    }
+    /// Shows the leaves of a meta-node individually, returning false if it wasn't collapsed.
+    pub fn expand(&mut self, meta: DAGID) -> bool {
+        match meta.checked_sub(self.graph.num_nodes()) {
+            Some(i) if i < self.groups.len() => self.set_collapsed(i, false),
+            _ => false,
+        }
+    }
+    /// Collapses the leaves of `parent` back into its meta-node, returning false if it has no
+    /// group or it was already collapsed.
+    pub fn collapse(&mut self, parent: DAGID) -> bool {
+        match self.group_of.get(&parent) {
+            Some(&i) => self.set_collapsed(i, true),
+            None => false,
+        }
+    }
+    fn set_collapsed(&mut self, i: usize, collapsed: bool) -> bool {
+        let group = &mut self.groups[i];
+        if group.collapsed == collapsed {
+            return false;
+        }
+        group.collapsed = collapsed;
+        for &leaf in &group.leaves {
+            self.hidden[leaf] = collapsed;
+        }
+        true
+    }
+}
+
+/// Neighbors of a node in an [`Aggregated`] graph.
+pub struct AggregatedNeighbors<'b, G: Graph + 'b> {
+    inner: Option<G::Neighbors<'b>>,
+    hidden: &'b [bool],
+    meta: Option<DAGID>,
+}
+
+impl<'b, G: Graph + 'b> Iterator for AggregatedNeighbors<'b, G> {
+    type Item = DAGID;
+    fn next(&mut self) -> Option<DAGID> {
+        if let Some(inner) = &mut self.inner {
+            if let Some(v) = inner.find(|&v| !self.hidden[v]) {
+                return Some(v);
+            }
+        }
+        self.meta.take()
+    }
+}
+
+impl<'a, G: Graph> Graph for Aggregated<'a, G> {
+    type Node = G::Node;
+    type Neighbors<'b>
+        = AggregatedNeighbors<'b, G>
+    where
+        Self: 'b;
+    fn num_nodes(&self) -> usize {
+        self.graph.num_nodes() + self.groups.len()
+    }
+    fn neighbors(&self, of: DAGID) -> Self::Neighbors<'_> {
+        let shown = of < self.graph.num_nodes() && !self.hidden[of];
+        let meta = self
+            .group_of
+            .get(&of)
+            .filter(|&&i| self.groups[i].collapsed);
+        AggregatedNeighbors {
+            inner: shown.then(|| self.graph.neighbors(of)),
+            hidden: &self.hidden,
+            meta: meta.map(|&i| self.meta_node(i)),
+        }
+    }
+    fn incoming(&self, to: DAGID) -> Self::Neighbors<'_> {
+        let shown = to < self.graph.num_nodes() && !self.hidden[to];
+        let parent = self.group(to).filter(|g| g.collapsed).map(|g| g.parent);
+        AggregatedNeighbors {
+            inner: shown.then(|| self.graph.incoming(to)),
+            hidden: &self.hidden,
+            meta: parent,
+        }
+    }
+    /// A collapsed meta-node has the data of the first leaf in its group.
+    fn node(&self, id: DAGID) -> Option<&G::Node> {
+        match self.group(id) {
+            Some(g) if g.collapsed => self.graph.node(g.leaves[0]),
+            Some(_) => None,
+            None if self.hidden.get(id) != Some(&false) => None,
+            None => self.graph.node(id),
+        }
+    }
+    /// The edge into a collapsed meta-node is that into the first leaf in its group.
+    fn find_edge(&self, from: DAGID, to: DAGID) -> Option<EdgeID> {
+        if self.group(from).is_some() || self.hidden[from] {
+            return None;
+        }
+        match self.group(to) {
+            Some(g) if g.collapsed && g.parent == from => self.graph.find_edge(from, g.leaves[0]),
+            Some(_) => None,
+            None if self.hidden[to] => None,
+            None => self.graph.find_edge(from, to),
+        }
+    }
+}
+
+#[test]
+fn test_aggregated() {
+    use crate::DAG;
+    // 0 has leaves 1..=10, and 11 which has a child 12.
+    let mut pairs: Vec<_> = (1..=10).map(|v| (0, v)).collect();
+    pairs.extend([(0, 11), (11, 12)]);
+    let dag = DAG::from_pairs(pairs);
+    let mut agg = Aggregated::new(&dag, 3);
+    assert_eq!(agg.groups().len(), 1);
+    let meta = agg.meta_node(0);
+    assert_eq!(meta, 13);
+    assert_eq!(agg.count(meta), 7);
+    assert_eq!(agg.neighbors(0).collect::<Vec<_>>(), [1, 2, 3, 11, meta]);
+    assert_eq!(agg.incoming(meta).collect::<Vec<_>>(), [0]);
+    assert_eq!(agg.node(5), None);
+    assert_eq!((agg.node(20), agg.count(20)), (None, 0));
+    assert_eq!(agg.find_edge(0, meta), dag.find_edge(0, 4));
+    assert_eq!(agg.find_edge(0, 5), None);
+
+    // The group takes one of the six wedges around the focus, so 11 is centered in the fourth.
+    let (coords, info) = crate::hyperbolic_project(&agg, 0);
+    let placed: Vec<_> = info.map(|v| v.dagid).collect();
+    assert_eq!(placed, [0, 1, 2, 3, 11, meta, 12]);
+    let [x, y] = coords[11];
+    assert!((y.atan2(x).to_degrees().rem_euclid(360.) - 210.).abs() < 1e-9);
+
+    assert!(agg.expand(meta));
+    assert!(!agg.expand(meta));
+    assert_eq!(agg.neighbors(0).count(), 11);
+    assert_eq!(agg.count(meta), 0);
+    assert!(agg.collapse(0));
+    assert_eq!(agg.neighbors(0).count(), 5);
+}
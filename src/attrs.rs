@@ -0,0 +1,299 @@
+//! Typed, column-oriented attribute storage for nodes or edges.
+
+use super::FP;
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttrType {
+    Bool,
+    Int,
+    Float,
+    Str,
+}
+
+/// Floats compare equal when their bits are, so that `NaN`s equal themselves and tables (and
+/// graphs holding them) can be `Eq`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Float(FP),
+    Str(String),
+}
+
+impl Value {
+    /// Parses `s` as a value of type `ty`.
+    pub fn parse(ty: AttrType, s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let v = match ty {
+            AttrType::Bool => match s {
+                "true" | "1" => Value::Bool(true),
+                "false" | "0" => Value::Bool(false),
+                _ => return Err(format!("invalid boolean {s:?}")),
+            },
+            AttrType::Int => Value::Int(s.parse().map_err(|e| format!("invalid int {s:?}: {e}"))?),
+            AttrType::Float => {
+                Value::Float(s.parse().map_err(|e| format!("invalid float {s:?}: {e}"))?)
+            }
+            AttrType::Str => Value::Str(s.to_string()),
+        };
+        Ok(v)
+    }
+    pub fn ty(&self) -> AttrType {
+        match self {
+            Value::Bool(_) => AttrType::Bool,
+            Value::Int(_) => AttrType::Int,
+            Value::Float(_) => AttrType::Float,
+            Value::Str(_) => AttrType::Str,
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a.to_bits() == b.to_bits(),
+            (Value::Str(a), Value::Str(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Value {}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Bool(v) => write!(f, "{v}"),
+            Value::Int(v) => write!(f, "{v}"),
+            Value::Float(v) => write!(f, "{v}"),
+            Value::Str(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+/// A single typed column, with `None` where the value is missing. Compares floats by their bits,
+/// like [`Value`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub enum Column {
+    Bool(Vec<Option<bool>>),
+    Int(Vec<Option<i64>>),
+    Float(Vec<Option<FP>>),
+    Str(Vec<Option<String>>),
+}
+
+impl PartialEq for Column {
+    fn eq(&self, other: &Self) -> bool {
+        let bits = |c: &[Option<FP>]| c.iter().map(|v| v.map(FP::to_bits)).collect::<Vec<_>>();
+        match (self, other) {
+            (Column::Bool(a), Column::Bool(b)) => a == b,
+            (Column::Int(a), Column::Int(b)) => a == b,
+            (Column::Float(a), Column::Float(b)) => bits(a) == bits(b),
+            (Column::Str(a), Column::Str(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Column {}
+
+impl Column {
+    fn new(ty: AttrType, len: usize) -> Self {
+        match ty {
+            AttrType::Bool => Column::Bool(vec![None; len]),
+            AttrType::Int => Column::Int(vec![None; len]),
+            AttrType::Float => Column::Float(vec![None; len]),
+            AttrType::Str => Column::Str(vec![None; len]),
+        }
+    }
+    pub fn ty(&self) -> AttrType {
+        match self {
+            Column::Bool(_) => AttrType::Bool,
+            Column::Int(_) => AttrType::Int,
+            Column::Float(_) => AttrType::Float,
+            Column::Str(_) => AttrType::Str,
+        }
+    }
+    pub fn len(&self) -> usize {
+        match self {
+            Column::Bool(c) => c.len(),
+            Column::Int(c) => c.len(),
+            Column::Float(c) => c.len(),
+            Column::Str(c) => c.len(),
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn resize(&mut self, len: usize) {
+        match self {
+            Column::Bool(c) => c.resize(len, None),
+            Column::Int(c) => c.resize(len, None),
+            Column::Float(c) => c.resize(len, None),
+            Column::Str(c) => c.resize(len, None),
+        }
+    }
+    /// The rows at `rows`, in that order.
+    fn select(&self, rows: &[usize]) -> Self {
+        match self {
+            Column::Bool(c) => Column::Bool(rows.iter().map(|&r| c[r]).collect()),
+            Column::Int(c) => Column::Int(rows.iter().map(|&r| c[r]).collect()),
+            Column::Float(c) => Column::Float(rows.iter().map(|&r| c[r]).collect()),
+            Column::Str(c) => Column::Str(rows.iter().map(|&r| c[r].clone()).collect()),
+        }
+    }
+    pub fn get(&self, i: usize) -> Option<Value> {
+        match self {
+            Column::Bool(c) => c.get(i)?.map(Value::Bool),
+            Column::Int(c) => c.get(i)?.map(Value::Int),
+            Column::Float(c) => c.get(i)?.map(Value::Float),
+            Column::Str(c) => c.get(i)?.clone().map(Value::Str),
+        }
+    }
+}
+
+/// Named columns of equal length, one row per node or edge.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawAttrTable"))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AttrTable {
+    len: usize,
+    columns: BTreeMap<String, Column>,
+}
+
+/// An [`AttrTable`] as deserialized, before its columns are checked to have `len` rows.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RawAttrTable {
+    len: usize,
+    columns: BTreeMap<String, Column>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawAttrTable> for AttrTable {
+    type Error = String;
+    fn try_from(raw: RawAttrTable) -> Result<Self, String> {
+        for (name, col) in &raw.columns {
+            if col.len() != raw.len {
+                return Err(format!(
+                    "column {name:?} has {} of {} rows",
+                    col.len(),
+                    raw.len
+                ));
+            }
+        }
+        Ok(Self {
+            len: raw.len,
+            columns: raw.columns,
+        })
+    }
+}
+
+impl AttrTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Number of rows.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Grows or shrinks every column to `len` rows, filling with missing values.
+    pub fn resize(&mut self, len: usize) {
+        self.len = len;
+        for c in self.columns.values_mut() {
+            c.resize(len);
+        }
+    }
+    /// A table of the rows at `rows`, in that order, e.g. for the nodes kept in a subgraph.
+    pub fn select(&self, rows: &[usize]) -> Self {
+        Self {
+            len: rows.len(),
+            columns: self
+                .columns
+                .iter()
+                .map(|(k, c)| (k.clone(), c.select(rows)))
+                .collect(),
+        }
+    }
+    /// Adds an empty column, or returns the existing column of the same name.
+    pub fn add_column(&mut self, name: &str, ty: AttrType) -> &mut Column {
+        let len = self.len;
+        self.columns
+            .entry(name.to_string())
+            .or_insert_with(|| Column::new(ty, len))
+    }
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        self.columns.get(name)
+    }
+    pub fn columns(&self) -> impl Iterator<Item = (&str, &Column)> {
+        self.columns.iter().map(|(k, v)| (k.as_str(), v))
+    }
+    pub fn get(&self, name: &str, row: usize) -> Option<Value> {
+        self.columns.get(name)?.get(row)
+    }
+    /// Sets a value, creating the column if needed and growing the table to fit `row`.
+    /// Fails if the column exists with a different type.
+    pub fn set(&mut self, name: &str, row: usize, v: Value) -> Result<(), String> {
+        if row >= self.len {
+            self.resize(row + 1);
+        }
+        let col = self.add_column(name, v.ty());
+        match (col, v) {
+            (Column::Bool(c), Value::Bool(v)) => c[row] = Some(v),
+            (Column::Int(c), Value::Int(v)) => c[row] = Some(v),
+            (Column::Float(c), Value::Float(v)) => c[row] = Some(v),
+            (Column::Str(c), Value::Str(v)) => c[row] = Some(v),
+            (c, v) => {
+                return Err(format!(
+                    "cannot store {:?} in {name:?} of type {:?}",
+                    v.ty(),
+                    c.ty()
+                ))
+            }
+        }
+        Ok(())
+    }
+    /// The float column `name`, with integer columns converted.
+    pub fn floats(&self, name: &str) -> Option<Vec<Option<FP>>> {
+        match self.columns.get(name)? {
+            Column::Float(c) => Some(c.clone()),
+            Column::Int(c) => Some(c.iter().map(|v| v.map(|v| v as FP)).collect()),
+            _ => None,
+        }
+    }
+    pub fn strs(&self, name: &str) -> Option<&[Option<String>]> {
+        match self.columns.get(name)? {
+            Column::Str(c) => Some(c),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn test_attr_table() {
+    let mut t = AttrTable::new();
+    t.set("weight", 2, Value::Float(0.5)).unwrap();
+    t.set("label", 0, Value::Str("a".into())).unwrap();
+    assert_eq!(t.len(), 3);
+    assert_eq!(t.floats("weight").unwrap(), [None, None, Some(0.5)]);
+    assert_eq!(t.get("label", 0), Some(Value::Str("a".into())));
+    assert_eq!(t.get("label", 1), None);
+    assert!(t.set("weight", 0, Value::Str("x".into())).is_err());
+    assert_eq!(Value::parse(AttrType::Int, " 42"), Ok(Value::Int(42)));
+    assert_eq!(Value::Float(FP::NAN), Value::Float(FP::NAN));
+    assert_ne!(Value::Float(0.), Value::Float(-0.));
+
+    let t = t.select(&[2, 0]);
+    assert_eq!(t.len(), 2);
+    assert_eq!(t.floats("weight").unwrap(), [Some(0.5), None]);
+    assert_eq!(t.strs("label").unwrap(), [None, Some("a".into())]);
+}
@@ -0,0 +1,157 @@
+//! Node centrality measures, used to pick a focus for the initial view of a graph.
+
+use super::dag::DAGID;
+use super::generate::Rng;
+use super::graph::{Direction, Graph};
+use super::FP;
+use std::collections::VecDeque;
+
+/// Number of sources sampled by [`suggest_focus`] on graphs with more nodes than this.
+const FOCUS_SAMPLES: usize = 64;
+
+/// Nodes adjacent to `v` along edges in `dir`.
+fn adjacent<G: Graph>(g: &G, v: DAGID, dir: Direction) -> impl Iterator<Item = DAGID> + '_ {
+    let out = dir.outgoing().then(|| g.neighbors(v));
+    let inc = dir.incoming().then(|| g.incoming(v));
+    out.into_iter().flatten().chain(inc.into_iter().flatten())
+}
+
+/// Number of edges into and out of each node.
+pub fn degree<G: Graph>(g: &G) -> Vec<usize> {
+    (0..g.num_nodes())
+        .map(|v| adjacent(g, v, Direction::Both).count())
+        .collect()
+}
+
+/// PageRank of each node along outgoing edges, summing to 1, after `iterations` rounds of power
+/// iteration. Rank flowing out of nodes without outgoing edges is spread over all nodes.
+pub fn pagerank<G: Graph>(g: &G, damping: FP, iterations: usize) -> Vec<FP> {
+    let present: Vec<_> = (0..g.num_nodes()).map(|v| g.node(v).is_some()).collect();
+    let n = present.iter().filter(|&&p| p).count();
+    if n == 0 {
+        return vec![0.; g.num_nodes()];
+    }
+    let out_degree: Vec<_> = (0..g.num_nodes()).map(|v| g.neighbors(v).count()).collect();
+    let uniform = |v: usize| if present[v] { 1. / n as FP } else { 0. };
+    let mut rank: Vec<_> = (0..g.num_nodes()).map(uniform).collect();
+    for _ in 0..iterations {
+        let mut next = vec![0.; g.num_nodes()];
+        let mut dangling = 0.;
+        for v in (0..g.num_nodes()).filter(|&v| present[v]) {
+            if out_degree[v] == 0 {
+                dangling += rank[v];
+                continue;
+            }
+            let share = rank[v] / out_degree[v] as FP;
+            for u in g.neighbors(v) {
+                next[u] += share;
+            }
+        }
+        for (v, r) in next.iter_mut().enumerate() {
+            *r = damping * (*r + dangling * uniform(v)) + (1. - damping) * uniform(v);
+        }
+        rank = next;
+    }
+    rank
+}
+
+/// Betweenness centrality following edges in `dir`: the number of shortest paths between
+/// ordered pairs of other nodes passing through each node, split evenly among ties, by Brandes'
+/// algorithm. With `samples`, only paths from that many random sources are counted and scaled
+/// up, estimating it in time linear in the size of the graph.
+pub fn betweenness<G: Graph>(g: &G, dir: Direction, samples: Option<usize>) -> Vec<FP> {
+    let n = g.num_nodes();
+    let mut centrality = vec![0.; n];
+    if n == 0 {
+        return centrality;
+    }
+    let sources: Vec<_> = match samples {
+        Some(k) => {
+            let mut rng = Rng::new(0);
+            (0..k).map(|_| rng.below(n)).collect()
+        }
+        None => (0..n).collect(),
+    };
+
+    let mut dist = vec![None; n];
+    let mut paths = vec![0.; n];
+    let mut preds = vec![vec![]; n];
+    let mut dependency = vec![0.; n];
+    let mut order = vec![];
+    for &s in sources.iter().filter(|&&s| g.node(s).is_some()) {
+        for v in order.drain(..) {
+            dist[v] = None;
+            paths[v] = 0.;
+            dependency[v] = 0.;
+            preds[v] = vec![];
+        }
+        dist[s] = Some(0);
+        paths[s] = 1.;
+        let mut work = VecDeque::from([s]);
+        while let Some(v) = work.pop_front() {
+            order.push(v);
+            let d = dist[v].unwrap() + 1;
+            for w in adjacent(g, v, dir) {
+                if dist[w].is_none() {
+                    dist[w] = Some(d);
+                    work.push_back(w);
+                }
+                if dist[w] == Some(d) {
+                    paths[w] += paths[v];
+                    preds[w].push(v);
+                }
+            }
+        }
+        // Accumulate dependencies from the farthest nodes back towards the source.
+        for &w in order.iter().rev() {
+            for &v in &preds[w] {
+                dependency[v] += paths[v] / paths[w] * (1. + dependency[w]);
+            }
+            if w != s {
+                centrality[w] += dependency[w];
+            }
+        }
+    }
+    if samples.is_some() {
+        let scale = n as FP / sources.len().max(1) as FP;
+        centrality.iter_mut().for_each(|c| *c *= scale);
+    }
+    centrality
+}
+
+/// A node to focus on in the initial view: the one with the highest betweenness with edges
+/// undirected, which tends to minimize how deep the rest of the graph is laid out, with ties
+/// broken by degree. `None` if the graph has no nodes.
+pub fn suggest_focus<G: Graph>(g: &G) -> Option<DAGID> {
+    let samples = (g.num_nodes() > FOCUS_SAMPLES).then_some(FOCUS_SAMPLES);
+    let centrality = betweenness(g, Direction::Both, samples);
+    let degree = degree(g);
+    (0..g.num_nodes())
+        .filter(|&v| g.node(v).is_some())
+        .max_by(|&a, &b| {
+            let key = |v: DAGID| (centrality[v], degree[v]);
+            key(a).partial_cmp(&key(b)).unwrap()
+        })
+}
+
+#[test]
+fn test_centrality() {
+    use crate::DAG;
+    // A path 0 - 1 - 2 - 3 - 4 with a leaf 5 hanging off 1.
+    let dag = DAG::from_pairs([(0, 1), (1, 2), (2, 3), (3, 4), (1, 5)]);
+    assert_eq!(degree(&dag), [1, 3, 2, 2, 1, 1]);
+    let exact = betweenness(&dag, Direction::Both, None);
+    assert_eq!(exact, [0., 14., 12., 8., 0., 0.]);
+    assert_eq!(betweenness(&dag, Direction::Outgoing, None)[2], 4.);
+    assert_eq!(suggest_focus(&dag), Some(1));
+
+    let rank = pagerank(&dag, 0.85, 50);
+    assert!((rank.iter().sum::<FP>() - 1.).abs() < 1e-9);
+    assert!(rank[4] > rank[3] && rank[3] > rank[0]);
+
+    // Sampling every node once in a large enough sample gives roughly the exact values.
+    let approx = betweenness(&dag, Direction::Both, Some(600));
+    for (a, e) in approx.iter().zip(&exact) {
+        assert!((a - e).abs() < 0.2 * e.max(1.), "{approx:?}");
+    }
+}
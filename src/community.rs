@@ -0,0 +1,200 @@
+//! Hierarchical community detection, to build a tree to project from graphs without a natural
+//! root.
+
+use super::dag::{DAG, DAGID};
+use super::graph::Graph;
+use super::FP;
+use std::collections::HashMap;
+
+/// A weighted undirected graph of communities, one level of the Louvain method.
+struct Level {
+    /// Weight of the edges to each other node.
+    adj: Vec<Vec<(usize, FP)>>,
+    /// Weight of the edges within each node, counted from both ends.
+    inner: Vec<FP>,
+}
+
+impl Level {
+    fn from_graph<G: Graph>(g: &G) -> Self {
+        let n = g.num_nodes();
+        let mut weights = vec![HashMap::new(); n];
+        for u in 0..n {
+            for v in g.neighbors(u).filter(|&v| v != u) {
+                *weights[u].entry(v).or_insert(0.) += 1.;
+                *weights[v].entry(u).or_insert(0.) += 1.;
+            }
+        }
+        Self::from_weights(weights, vec![0.; n])
+    }
+    fn from_weights(weights: Vec<HashMap<usize, FP>>, inner: Vec<FP>) -> Self {
+        let adj = weights
+            .into_iter()
+            .map(|w| {
+                let mut w: Vec<_> = w.into_iter().collect();
+                // Sorted so that results don't depend on hash order.
+                w.sort_by_key(|&(v, _)| v);
+                w
+            })
+            .collect();
+        Self { adj, inner }
+    }
+    fn degree(&self, v: usize) -> FP {
+        self.inner[v] + self.adj[v].iter().map(|&(_, w)| w).sum::<FP>()
+    }
+    /// Greedily moves each node to the neighboring community which most increases modularity
+    /// until none moves. Returns the community of each node numbered from 0, or `None` if no
+    /// node moved.
+    fn local_moves(&self, resolution: FP) -> Option<Vec<usize>> {
+        let n = self.adj.len();
+        let degree: Vec<_> = (0..n).map(|v| self.degree(v)).collect();
+        let total: FP = degree.iter().sum();
+        if total == 0. {
+            return None;
+        }
+        let mut community: Vec<_> = (0..n).collect();
+        let mut community_degree = degree.clone();
+        let mut moved = false;
+        let mut links = HashMap::new();
+        loop {
+            let mut changed = false;
+            for v in 0..n {
+                let current = community[v];
+                community_degree[current] -= degree[v];
+                links.clear();
+                for &(u, w) in &self.adj[v] {
+                    *links.entry(community[u]).or_insert(0.) += w;
+                }
+                let gain = |c: usize, links: FP| {
+                    links - resolution * community_degree[c] * degree[v] / total
+                };
+                let mut best = (
+                    current,
+                    gain(current, links.get(&current).copied().unwrap_or(0.)),
+                );
+                let mut candidates: Vec<_> = links.iter().map(|(&c, &w)| (c, w)).collect();
+                candidates.sort_by_key(|&(c, _)| c);
+                for (c, w) in candidates {
+                    let g = gain(c, w);
+                    if g > best.1 + 1e-12 {
+                        best = (c, g);
+                    }
+                }
+                community[v] = best.0;
+                community_degree[best.0] += degree[v];
+                changed |= best.0 != current;
+            }
+            if !changed {
+                break;
+            }
+            moved = true;
+        }
+        if !moved {
+            return None;
+        }
+        let mut renumber = HashMap::new();
+        for c in &mut community {
+            let next = renumber.len();
+            *c = *renumber.entry(*c).or_insert(next);
+        }
+        Some(community)
+    }
+    /// The graph of communities, with edges within a community kept as its inner weight.
+    fn aggregate(&self, community: &[usize]) -> Self {
+        let count = community.iter().max().map_or(0, |&c| c + 1);
+        let mut weights = vec![HashMap::new(); count];
+        let mut inner = vec![0.; count];
+        for (v, adj) in self.adj.iter().enumerate() {
+            let c = community[v];
+            inner[c] += self.inner[v];
+            for &(u, w) in adj {
+                if community[u] == c {
+                    inner[c] += w;
+                } else {
+                    *weights[c].entry(community[u]).or_insert(0.) += w;
+                }
+            }
+        }
+        Self::from_weights(weights, inner)
+    }
+}
+
+/// Communities of a graph with edges undirected, by the Louvain method: greedily moving nodes
+/// between communities to maximize modularity, then repeating on the graph of communities.
+/// Larger `resolution`s give smaller communities, and 1 is standard modularity.
+///
+/// Returns the community of each node at each level, numbered from 0, with the coarsest last.
+pub fn louvain<G: Graph>(g: &G, resolution: FP) -> Vec<Vec<usize>> {
+    let mut level = Level::from_graph(g);
+    let mut membership: Vec<_> = (0..g.num_nodes()).collect();
+    let mut levels = vec![];
+    while let Some(community) = level.local_moves(resolution) {
+        level = level.aggregate(&community);
+        for c in &mut membership {
+            *c = community[*c];
+        }
+        levels.push(membership.clone());
+    }
+    levels
+}
+
+/// The hierarchy of communities from [`louvain`] as a tree, with edges from each community to
+/// the communities and nodes in it. Communities with a single member are left out.
+///
+/// The first `g.num_nodes()` nodes are the leaves, with the DAGIDs of the nodes of `g` as their
+/// data, and communities follow with no data. The root is the last node.
+pub fn community_tree<G: Graph>(g: &G, resolution: FP) -> DAG<Option<DAGID>> {
+    let mut tree = DAG::new();
+    for v in 0..g.num_nodes() {
+        tree.insert(Some(v));
+    }
+    // Node of the tree standing for each community of the previous level.
+    let mut ids: Vec<DAGID> = (0..g.num_nodes()).collect();
+    let mut prev: Vec<usize> = (0..g.num_nodes()).collect();
+    let mut levels = louvain(g, resolution);
+    // Everything is in one community at the top.
+    levels.push(vec![0; g.num_nodes()]);
+    for membership in levels {
+        let count = membership.iter().max().map_or(0, |&c| c + 1);
+        let mut members = vec![vec![]; count];
+        let mut seen = vec![false; ids.len()];
+        for (v, &c) in membership.iter().enumerate() {
+            if !std::mem::replace(&mut seen[prev[v]], true) {
+                members[c].push(ids[prev[v]]);
+            }
+        }
+        ids = members
+            .into_iter()
+            .map(|members| match members[..] {
+                [only] => only,
+                _ => {
+                    let id = tree.insert(None);
+                    for m in members {
+                        tree.insert_edge(id, m);
+                    }
+                    id
+                }
+            })
+            .collect();
+        prev = membership;
+    }
+    tree
+}
+
+#[test]
+fn test_community_tree() {
+    // Two triangles joined by the edge 2 - 3, and 6 on its own.
+    let mut dag = DAG::from_pairs([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)]);
+    dag.insert(6);
+    let levels = louvain(&dag, 1.);
+    assert_eq!(levels, [[0, 0, 0, 1, 1, 1, 2]]);
+
+    let tree = community_tree(&dag, 1.);
+    assert_eq!(tree.num_nodes(), 10);
+    assert_eq!(tree.get(4), &Some(4));
+    assert_eq!(tree.neighbors(7), [0, 1, 2]);
+    assert_eq!(tree.neighbors(8), [3, 4, 5]);
+    assert_eq!(tree.neighbors(9), [7, 8, 6]);
+
+    let placed = crate::hyperbolic_project(&tree, 9).1.count();
+    assert_eq!(placed, tree.num_nodes());
+}
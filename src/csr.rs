@@ -0,0 +1,230 @@
+use super::dag::{EdgeID, DAG, DAGID};
+use super::graph::Graph;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::iter::Copied;
+use std::slice;
+
+/// A frozen graph with adjacency stored in compressed sparse row form: all edges in one flat
+/// array, with each node's edges found through an offset array. Compared to [`DAG`], this avoids
+/// two allocations per node and keeps neighbors contiguous, which matters for very large graphs.
+///
+/// Edges keep the [`EdgeID`]s they were given, their position in the edge list it was built
+/// from. Serialized as its nodes' data and that edge list; the offsets are rebuilt and checked
+/// when deserializing.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct CsrDag<T> {
+    elements: Vec<T>,
+    /// Outgoing edges of node `i` are `targets[out_offsets[i]..out_offsets[i + 1]]`.
+    out_offsets: Vec<usize>,
+    targets: Vec<DAGID>,
+    /// The [`EdgeID`] of each edge in `targets`.
+    edge_ids: Vec<EdgeID>,
+    /// Incoming edges of node `i` are `sources[in_offsets[i]..in_offsets[i + 1]]`.
+    in_offsets: Vec<usize>,
+    sources: Vec<DAGID>,
+}
+
+/// Groups `(key, value)` pairs by key, keeping the order values were given in.
+fn group_by_key<V: Copy + Default>(
+    n: usize,
+    pairs: impl Iterator<Item = (DAGID, V)> + Clone,
+) -> (Vec<usize>, Vec<V>) {
+    let mut offsets = vec![0; n + 1];
+    for (k, _) in pairs.clone() {
+        offsets[k + 1] += 1;
+    }
+    for i in 0..n {
+        offsets[i + 1] += offsets[i];
+    }
+    let mut next = offsets.clone();
+    let mut values = vec![V::default(); offsets[n]];
+    for (k, v) in pairs {
+        values[next[k]] = v;
+        next[k] += 1;
+    }
+    (offsets, values)
+}
+
+impl<T> CsrDag<T> {
+    /// Builds from the nodes' data and edges, in the order they should appear in adjacency lists.
+    /// Each edge's [`EdgeID`] is its index in `edges`.
+    pub fn from_edges(elements: Vec<T>, edges: &[(DAGID, DAGID)]) -> Self {
+        let n = elements.len();
+        let out = edges.iter().enumerate().map(|(e, &(s, d))| (s, (d, e)));
+        let (out_offsets, out) = group_by_key(n, out);
+        let (targets, edge_ids) = out.into_iter().unzip();
+        let (in_offsets, sources) = group_by_key(n, edges.iter().map(|&(s, d)| (d, s)));
+        Self {
+            elements,
+            out_offsets,
+            targets,
+            edge_ids,
+            in_offsets,
+            sources,
+        }
+    }
+    pub fn from_pairs(pairs: impl IntoIterator<Item = (T, T)>) -> Self
+    where
+        T: Hash + Eq + Clone,
+    {
+        let mut seen = HashMap::new();
+        let mut elements = vec![];
+        let mut id = |v: T| {
+            *seen.entry(v).or_insert_with_key(|v| {
+                elements.push(v.clone());
+                elements.len() - 1
+            })
+        };
+        let edges: Vec<_> = pairs.into_iter().map(|(s, d)| (id(s), id(d))).collect();
+        Self::from_edges(elements, &edges)
+    }
+    pub fn num_nodes(&self) -> usize {
+        self.elements.len()
+    }
+    pub fn num_edges(&self) -> usize {
+        self.targets.len()
+    }
+    /// All vertices `v` such that `of` -> `v`.
+    pub fn neighbors(&self, of: DAGID) -> &[DAGID] {
+        &self.targets[self.out_offsets[of]..self.out_offsets[of + 1]]
+    }
+    /// IDs of the edges from `of`, in the same order as [`CsrDag::neighbors`].
+    pub fn out_edges(&self, of: DAGID) -> &[EdgeID] {
+        &self.edge_ids[self.out_offsets[of]..self.out_offsets[of + 1]]
+    }
+    /// All vertices `v` such that `v` -> `to`.
+    pub fn incoming(&self, to: DAGID) -> &[DAGID] {
+        &self.sources[self.in_offsets[to]..self.in_offsets[to + 1]]
+    }
+    /// returns data for an ID.
+    pub fn get(&self, id: DAGID) -> &T {
+        &self.elements[id]
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CsrRepr<N, E> {
+    nodes: N,
+    edges: E,
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for CsrDag<T> {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut edges = vec![(0, 0); self.num_edges()];
+        for i in 0..self.num_nodes() {
+            for (&j, &e) in self.neighbors(i).iter().zip(self.out_edges(i)) {
+                edges[e] = (i, j);
+            }
+        }
+        CsrRepr {
+            nodes: &self.elements,
+            edges,
+        }
+        .serialize(s)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for CsrDag<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let repr: CsrRepr<Vec<T>, Vec<(DAGID, DAGID)>> = CsrRepr::deserialize(d)?;
+        let n = repr.nodes.len();
+        if let Some(&(from, to)) = repr.edges.iter().find(|&&(s, d)| s >= n || d >= n) {
+            return Err(serde::de::Error::custom(format!(
+                "edge {from} -> {to} out of range for {n} nodes"
+            )));
+        }
+        Ok(Self::from_edges(repr.nodes, &repr.edges))
+    }
+}
+
+impl<T> Graph for CsrDag<T> {
+    type Node = T;
+    type Neighbors<'a>
+        = Copied<slice::Iter<'a, DAGID>>
+    where
+        T: 'a;
+    fn num_nodes(&self) -> usize {
+        self.num_nodes()
+    }
+    fn neighbors(&self, of: DAGID) -> Self::Neighbors<'_> {
+        self.neighbors(of).iter().copied()
+    }
+    fn incoming(&self, to: DAGID) -> Self::Neighbors<'_> {
+        self.incoming(to).iter().copied()
+    }
+    fn node(&self, id: DAGID) -> Option<&T> {
+        self.elements.get(id)
+    }
+    fn find_edge(&self, from: DAGID, to: DAGID) -> Option<EdgeID> {
+        let i = self.neighbors(from).iter().position(|&v| v == to)?;
+        Some(self.out_edges(from)[i])
+    }
+}
+
+impl<T: Clone> From<&DAG<T>> for CsrDag<T> {
+    fn from(dag: &DAG<T>) -> Self {
+        let elements = (0..dag.num_nodes()).map(|i| dag.get(i).clone()).collect();
+        // Adjacency lists of a DAG are in insertion order, which is also EdgeID order.
+        let edges: Vec<_> = (0..dag.num_edges()).map(|e| dag.edge(e)).collect();
+        Self::from_edges(elements, &edges)
+    }
+}
+
+#[test]
+fn test_csr_matches_dag() {
+    let dag = crate::generate::citation_dag(300, 3, 2);
+    let csr = CsrDag::from(&dag);
+    assert_eq!(csr.num_nodes(), dag.num_nodes());
+    assert_eq!(csr.num_edges(), dag.num_edges());
+    for i in 0..dag.num_nodes() {
+        assert_eq!(csr.neighbors(i), dag.neighbors(i));
+        assert_eq!(csr.incoming(i), dag.incoming(i));
+        assert_eq!(csr.get(i), dag.get(i));
+    }
+    let (mut a, mut b) = (vec![], vec![]);
+    dag.breadth_first_visit(7, crate::Direction::Both, |d| a.push(d));
+    csr.breadth_first_visit(7, crate::Direction::Both, |d| b.push(d));
+    assert_eq!(a, b);
+
+    // Edge lengths are looked up by the DAG's EdgeIDs.
+    let lengths: Vec<_> = (0..dag.num_edges())
+        .map(|e| (e % 7) as crate::FP * 0.3)
+        .collect();
+    let opts = crate::ProjectOptions {
+        edge_lengths: Some(&lengths),
+        ..Default::default()
+    };
+    for focus in [0, 7, 150] {
+        let a = crate::hyperbolic_project_with(&dag, focus, &opts).0;
+        let b = crate::hyperbolic_project_with(&csr, focus, &opts).0;
+        assert_eq!(a, b);
+    }
+
+    let pairs = [(5, 1), (1, 2), (5, 2), (2, 5), (1, 1)];
+    let (dag, csr) = (DAG::from_pairs(pairs), CsrDag::from_pairs(pairs));
+    for i in 0..dag.num_nodes() {
+        assert_eq!(csr.neighbors(i), dag.neighbors(i));
+        assert_eq!(csr.incoming(i), dag.incoming(i));
+        assert_eq!(csr.out_edges(i), dag.out_edges(i));
+    }
+    for e in 0..dag.num_edges() {
+        let (s, d) = dag.edge(e);
+        assert_eq!(Graph::find_edge(&csr, s, d), Some(e));
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_csr_serde() {
+    let csr = CsrDag::from_pairs([(5, 1), (1, 2), (5, 2)]);
+    let s = serde_json::to_string(&csr).unwrap();
+    assert_eq!(s, r#"{"nodes":[5,1,2],"edges":[[0,1],[1,2],[0,2]]}"#);
+    let back: CsrDag<i32> = serde_json::from_str(&s).unwrap();
+    assert_eq!(back, csr);
+    assert_eq!(back.incoming(2), [1, 0]);
+    assert!(serde_json::from_str::<CsrDag<i32>>(r#"{"nodes":[1],"edges":[[0,1]]}"#).is_err());
+}
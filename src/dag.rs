@@ -1,18 +1,74 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use super::attrs::AttrTable;
+use super::graph::Graph;
+use super::FP;
+use std::collections::HashMap;
+use std::fmt;
 use std::hash::Hash;
+use std::iter::Copied;
+use std::slice;
 
-#[derive(Debug, PartialEq, Eq, Clone, Default)]
+/// Serialized as its nodes' data, its edges and its attribute tables; the adjacency indices are
+/// rebuilt and checked when deserializing. Graphs are equal if those are, whatever their
+/// [`EdgePolicy`].
+#[derive(Debug, Clone, Default)]
 pub struct DAG<T> {
     elements: Vec<T>,
     /// Outgoing edges
     edges: Vec<Vec<DAGID>>,
     /// Backwards edges, does not exist but refers to the edge inverse.
     back_edges: Vec<Vec<DAGID>>,
+    /// IDs of the edges in `edges` and `back_edges`.
+    edge_ids: Vec<Vec<EdgeID>>,
+    back_edge_ids: Vec<Vec<EdgeID>>,
+    /// Source and target of each edge, indexed by EdgeID.
+    endpoints: Vec<(DAGID, DAGID)>,
+    /// The edge between each pair of connected nodes, only kept when the policy needs to look
+    /// them up on every insertion.
+    edge_lookup: HashMap<(DAGID, DAGID), EdgeID>,
+    edge_policy: EdgePolicy,
+    /// Attributes with one row per node.
+    node_attrs: AttrTable,
+    /// Attributes with one row per edge.
+    edge_attrs: AttrTable,
 }
 
 /// Alias for usize for an item inserted into a tree.
 pub type DAGID = usize;
 
+/// Index of an edge, in the order edges were inserted.
+pub type EdgeID = usize;
+
+/// What [`DAG::insert_edge`] does with duplicate edges and self-loops.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EdgePolicy {
+    /// Inserts them like any other edge.
+    #[default]
+    Allow,
+    /// Silently drops them.
+    Dedupe,
+    /// Fails with an [`EdgeError`].
+    Reject,
+}
+
+/// An edge refused by [`EdgePolicy::Reject`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeError {
+    SelfLoop(DAGID),
+    Duplicate(DAGID, DAGID),
+}
+
+impl fmt::Display for EdgeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EdgeError::SelfLoop(v) => write!(f, "self-loop on node {v}"),
+            EdgeError::Duplicate(from, to) => write!(f, "duplicate edge {from} -> {to}"),
+        }
+    }
+}
+
+impl std::error::Error for EdgeError {}
+
 impl<T> DAG<T> {
     pub fn new() -> Self {
         Self {
@@ -20,25 +76,124 @@ impl<T> DAG<T> {
 
             edges: vec![],
             back_edges: vec![],
+            edge_ids: vec![],
+            back_edge_ids: vec![],
+            endpoints: vec![],
+            edge_lookup: HashMap::new(),
+            edge_policy: EdgePolicy::Allow,
+            node_attrs: AttrTable::new(),
+            edge_attrs: AttrTable::new(),
         }
     }
+    /// An empty graph which treats duplicate edges and self-loops according to `policy`.
+    pub fn with_edge_policy(policy: EdgePolicy) -> Self {
+        Self {
+            edge_policy: policy,
+            ..Self::new()
+        }
+    }
+    pub fn edge_policy(&self) -> EdgePolicy {
+        self.edge_policy
+    }
     pub fn num_nodes(&self) -> usize {
         self.elements.len()
     }
     pub fn num_edges(&self) -> usize {
-        self.edges.iter().map(|e| e.len()).sum()
+        self.endpoints.len()
     }
     pub fn insert(&mut self, v: T) -> DAGID {
         let idx = self.elements.len();
         self.elements.push(v);
         self.edges.push(Vec::new());
         self.back_edges.push(Vec::new());
+        self.edge_ids.push(Vec::new());
+        self.back_edge_ids.push(Vec::new());
+        self.node_attrs.resize(idx + 1);
 
         idx
     }
-    pub fn insert_edge(&mut self, from: DAGID, to: DAGID) {
+    /// Inserts an edge, returning its ID, or `None` if it is dropped by [`EdgePolicy::Dedupe`].
+    ///
+    /// Panics if the edge is refused by [`EdgePolicy::Reject`], see [`DAG::try_insert_edge`].
+    pub fn insert_edge(&mut self, from: DAGID, to: DAGID) -> Option<EdgeID> {
+        self.try_insert_edge(from, to)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+    /// Inserts an edge, failing if it is refused by [`EdgePolicy::Reject`].
+    pub fn try_insert_edge(&mut self, from: DAGID, to: DAGID) -> Result<Option<EdgeID>, EdgeError> {
+        if self.edge_policy != EdgePolicy::Allow {
+            let err = if from == to {
+                Some(EdgeError::SelfLoop(from))
+            } else if self.has_edge(from, to) {
+                Some(EdgeError::Duplicate(from, to))
+            } else {
+                None
+            };
+            match err {
+                Some(e) if self.edge_policy == EdgePolicy::Reject => return Err(e),
+                Some(_) => return Ok(None),
+                None => {}
+            }
+        }
+        let id = self.endpoints.len();
         self.edges[from].push(to);
         self.back_edges[to].push(from);
+        self.edge_ids[from].push(id);
+        self.back_edge_ids[to].push(id);
+        self.endpoints.push((from, to));
+        if self.edge_policy != EdgePolicy::Allow {
+            self.edge_lookup.insert((from, to), id);
+        }
+        self.edge_attrs.resize(id + 1);
+        Ok(Some(id))
+    }
+    /// Whether there is an edge `from` -> `to`, see [`DAG::find_edge`].
+    pub fn has_edge(&self, from: DAGID, to: DAGID) -> bool {
+        self.find_edge(from, to).is_some()
+    }
+    /// The first edge inserted from `from` to `to`, if there is one. Takes O(1) unless the
+    /// policy is [`EdgePolicy::Allow`], in which case the edges from `from` are scanned.
+    pub fn find_edge(&self, from: DAGID, to: DAGID) -> Option<EdgeID> {
+        if self.edge_policy != EdgePolicy::Allow {
+            return self.edge_lookup.get(&(from, to)).copied();
+        }
+        let i = self.edges[from].iter().position(|&v| v == to)?;
+        Some(self.edge_ids[from][i])
+    }
+    /// The source and target of an edge.
+    pub fn edge(&self, id: EdgeID) -> (DAGID, DAGID) {
+        self.endpoints[id]
+    }
+    /// IDs of the edges from `of`, in the same order as [`DAG::neighbors`].
+    pub fn out_edges(&self, of: DAGID) -> &[EdgeID] {
+        &self.edge_ids[of]
+    }
+    /// IDs of the edges into `to`, in the same order as [`DAG::incoming`].
+    pub fn in_edges(&self, to: DAGID) -> &[EdgeID] {
+        &self.back_edge_ids[to]
+    }
+    /// Node attributes, one row per DAGID.
+    pub fn node_attrs(&self) -> &AttrTable {
+        &self.node_attrs
+    }
+    /// Node attributes, which stay one row per node as nodes are inserted.
+    pub fn node_attrs_mut(&mut self) -> &mut AttrTable {
+        &mut self.node_attrs
+    }
+    /// Edge attributes, one row per EdgeID.
+    pub fn edge_attrs(&self) -> &AttrTable {
+        &self.edge_attrs
+    }
+    /// Edge attributes, which stay one row per edge as edges are inserted.
+    pub fn edge_attrs_mut(&mut self) -> &mut AttrTable {
+        &mut self.edge_attrs
+    }
+    /// Length of each edge from the numeric edge attribute `name`, for
+    /// [`ProjectOptions::edge_lengths`](crate::ProjectOptions). Edges with missing values have
+    /// length 1.
+    pub fn edge_lengths(&self, name: &str) -> Option<Vec<FP>> {
+        let weights = self.edge_attrs.floats(name)?;
+        Some(weights.into_iter().map(|w| w.unwrap_or(1.)).collect())
     }
     /// All vertices `v` such that `of` -> `v`.
     pub fn neighbors(&self, of: DAGID) -> &[DAGID] {
@@ -53,86 +208,41 @@ impl<T> DAG<T> {
     pub fn get(&self, id: DAGID) -> &T {
         &self.elements[id]
     }
-    pub fn depth_first_iter<const order: TraversalOrder>(
-        &self,
-        from: DAGID,
-    ) -> DepthFirstIter<'_, T, order> {
-        DepthFirstIter {
-            tree: self,
-            parents: vec![(from, 0)],
-            visited: HashSet::new(),
-        }
+    /// returns mutable data for an ID.
+    pub fn get_mut(&mut self, id: DAGID) -> &mut T {
+        &mut self.elements[id]
     }
-    pub fn depth_first_visit(&self, from: DAGID, mut f: impl FnMut(DFOut)) {
-        let mut work = vec![DFOut {
-            dagid: from,
-            depth: 0,
-            parent_ref: None,
-        }];
-        let mut seen = HashSet::new();
-
-        while let Some(node) = work.pop() {
-            if !seen.insert(node.dagid) {
-                continue;
-            }
-            let children = self
-                .neighbors(node.dagid)
-                .iter()
-                .enumerate()
-                .map(|(i, &dagid)| DFOut {
-                    dagid,
-                    depth: node.depth + 1,
-                    parent_ref: Some((node.dagid, i)),
-                })
-                .rev();
-            work.extend(children);
-            f(node);
-        }
-    }
-
-    pub fn breadth_first_visit(&self, from: DAGID, mut f: impl FnMut(DFOut)) {
-        let mut work = VecDeque::new();
-        work.push_back(DFOut {
-            dagid: from,
-            depth: 0,
-            parent_ref: None,
-        });
-        let mut seen = HashSet::new();
-
-        while let Some(node) = work.pop_front() {
-            if !seen.insert(node.dagid) {
-                continue;
-            }
-            let children = self
-                .neighbors(node.dagid)
-                .iter()
-                .enumerate()
-                .map(|(i, &dagid)| DFOut {
-                    dagid,
-                    depth: node.depth + 1,
-                    parent_ref: Some((node.dagid, i)),
-                });
-            let parents = self
-                .incoming(node.dagid)
-                .iter()
-                .enumerate()
-                .map(|(i, &dagid)| DFOut {
-                    dagid,
-                    depth: node.depth + 1,
-                    parent_ref: Some((node.dagid, i)),
-                });
-            work.extend(children);
-            work.extend(parents);
-            f(node);
+    /// Copies the structure and attributes of this graph, transforming each node's data.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> DAG<U> {
+        DAG {
+            elements: self.elements.iter().map(f).collect(),
+            edges: self.edges.clone(),
+            back_edges: self.back_edges.clone(),
+            edge_ids: self.edge_ids.clone(),
+            back_edge_ids: self.back_edge_ids.clone(),
+            endpoints: self.endpoints.clone(),
+            edge_lookup: self.edge_lookup.clone(),
+            edge_policy: self.edge_policy,
+            node_attrs: self.node_attrs.clone(),
+            edge_attrs: self.edge_attrs.clone(),
         }
     }
-
     pub fn from_pairs(pairs: impl IntoIterator<Item = (T, T)>) -> Self
+    where
+        T: Hash + Eq + Clone,
+    {
+        Self::from_pairs_with(pairs, EdgePolicy::Allow).unwrap()
+    }
+    /// [`DAG::from_pairs`], treating duplicate edges and self-loops according to `policy`.
+    pub fn from_pairs_with(
+        pairs: impl IntoIterator<Item = (T, T)>,
+        policy: EdgePolicy,
+    ) -> Result<Self, EdgeError>
     where
         T: Hash + Eq + Clone,
     {
         let mut seen = HashMap::new();
-        let mut out = Self::new();
+        let mut out = Self::with_edge_policy(policy);
         for (src, dst) in pairs {
             let src_id = *seen
                 .entry(src)
@@ -140,36 +250,197 @@ impl<T> DAG<T> {
             let dst_id = *seen
                 .entry(dst)
                 .or_insert_with_key(|dst| out.insert(dst.clone()));
-            out.insert_edge(src_id, dst_id);
+            out.try_insert_edge(src_id, dst_id)?;
         }
-        out
+        Ok(out)
+    }
+}
+
+impl<T: PartialEq> PartialEq for DAG<T> {
+    fn eq(&self, other: &Self) -> bool {
+        // The adjacency lists and lookup follow from the edges, in insertion order.
+        self.elements == other.elements
+            && self.endpoints == other.endpoints
+            && self.node_attrs == other.node_attrs
+            && self.edge_attrs == other.edge_attrs
+    }
+}
+
+impl<T: Eq> Eq for DAG<T> {}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct DagRef<'a, T> {
+    nodes: &'a [T],
+    edges: &'a [(DAGID, DAGID)],
+    node_attrs: &'a AttrTable,
+    edge_attrs: &'a AttrTable,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct DagRepr<T> {
+    nodes: Vec<T>,
+    edges: Vec<(DAGID, DAGID)>,
+    #[serde(default)]
+    node_attrs: AttrTable,
+    #[serde(default)]
+    edge_attrs: AttrTable,
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for DAG<T> {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        DagRef {
+            nodes: &self.elements,
+            edges: &self.endpoints,
+            node_attrs: &self.node_attrs,
+            edge_attrs: &self.edge_attrs,
+        }
+        .serialize(s)
     }
 }
 
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for DAG<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let repr = DagRepr::deserialize(d)?;
+        let mut out = DAG::new();
+        for v in repr.nodes {
+            out.insert(v);
+        }
+        let n = out.num_nodes();
+        for (from, to) in repr.edges {
+            if from >= n || to >= n {
+                return Err(D::Error::custom(format!(
+                    "edge {from} -> {to} out of range for {n} nodes"
+                )));
+            }
+            out.insert_edge(from, to);
+        }
+        for (name, table, len) in [
+            ("node", &repr.node_attrs, n),
+            ("edge", &repr.edge_attrs, out.num_edges()),
+        ] {
+            if table.len() > len {
+                return Err(D::Error::custom(format!(
+                    "{} {name} attribute rows for {len} {name}s",
+                    table.len()
+                )));
+            }
+        }
+        out.node_attrs = repr.node_attrs;
+        out.node_attrs.resize(n);
+        out.edge_attrs = repr.edge_attrs;
+        out.edge_attrs.resize(out.num_edges());
+        Ok(out)
+    }
+}
+
+impl<T> Graph for DAG<T> {
+    type Node = T;
+    type Neighbors<'a>
+        = Copied<slice::Iter<'a, DAGID>>
+    where
+        T: 'a;
+    fn num_nodes(&self) -> usize {
+        self.num_nodes()
+    }
+    fn neighbors(&self, of: DAGID) -> Self::Neighbors<'_> {
+        self.edges[of].iter().copied()
+    }
+    fn incoming(&self, to: DAGID) -> Self::Neighbors<'_> {
+        self.back_edges[to].iter().copied()
+    }
+    fn node(&self, id: DAGID) -> Option<&T> {
+        self.elements.get(id)
+    }
+    fn find_edge(&self, from: DAGID, to: DAGID) -> Option<EdgeID> {
+        self.find_edge(from, to)
+    }
+}
+
+/// When [`DepthFirstIter`] yields a node relative to its descendants.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum TraversalOrder {
+    /// Before any of its descendants.
     PreOrder,
+    /// After all of its descendants.
     PostOrder,
+    /// After the descendants through its first child, and before the rest.
+    InOrder,
+}
+
+/// Depth first traversal from a node, following outgoing edges.
+///
+/// Each reachable node is yielded exactly once, from the first edge which reaches it, so shared
+/// children and cycles are only descended into once.
+pub struct DepthFirstIter<'a, G: Graph + 'a> {
+    tree: &'a G,
+    order: TraversalOrder,
+    /// Path from the start to the node currently being visited.
+    stack: Vec<Frame<G::Neighbors<'a>>>,
+    visited: Vec<bool>,
 }
 
-#[derive(Debug, Clone)]
-pub struct DepthFirstIter<'a, T, const order: TraversalOrder> {
-    tree: &'a DAG<T>,
-    /// Stack of previous parents and which child it's currently visiting.
-    parents: Vec<(DAGID, usize)>,
-    visited: HashSet<DAGID>,
+struct Frame<I> {
+    out: DFOut,
+    children: std::iter::Enumerate<I>,
+    /// Whether this node has been yielded.
+    emitted: bool,
+    /// Whether any child has been descended into.
+    explored: bool,
 }
 
+impl<'a, G: Graph> DepthFirstIter<'a, G> {
+    pub fn new(tree: &'a G, from: DAGID, order: TraversalOrder) -> Self {
+        let mut visited = vec![false; tree.num_nodes()];
+        visited[from] = true;
+        let root = DFOut::root(from);
+        let mut out = Self {
+            tree,
+            order,
+            stack: vec![],
+            visited,
+        };
+        out.push(root);
+        out
+    }
+    fn push(&mut self, out: DFOut) {
+        self.stack.push(Frame {
+            out,
+            children: self.tree.neighbors(out.dagid).enumerate(),
+            emitted: false,
+            explored: false,
+        });
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct DFOut {
     pub dagid: DAGID,
 
-    /// Depth relative to a root, can be negative for parents of the root.
+    /// Number of edges from the root along the traversal.
     pub depth: usize,
+    /// Signed number of generations from the root: positive for descendants, negative for
+    /// ancestors. Each edge walked forwards adds one, and each walked backwards subtracts one.
+    pub generation: isize,
     /// Parent's DAGID, and index of this child is this
     pub parent_ref: Option<(DAGID, usize)>,
 }
 
+impl DFOut {
+    /// The start of a traversal.
+    pub fn root(dagid: DAGID) -> Self {
+        Self {
+            dagid,
+            ..Default::default()
+        }
+    }
+}
+
 impl PartialOrd for DFOut {
     fn partial_cmp(&self, o: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(o))
@@ -182,69 +453,59 @@ impl Ord for DFOut {
     }
 }
 
-impl<'a, T, const order: TraversalOrder> Iterator for DepthFirstIter<'a, T, order>
-where
-    T: Hash + Eq,
-{
+impl<'a, G: Graph> Iterator for DepthFirstIter<'a, G> {
     type Item = DFOut;
     fn next(&mut self) -> Option<Self::Item> {
-        while self.parents.len() > 0 {
-            let depth = self.parents.len() - 1;
-            let parent_ref: Option<(DAGID, usize)> = self
-                .parents
-                .len()
-                .checked_sub(2)
-                .and_then(|idx| self.parents.get(idx))
-                .map(|(parent, cn)| (*parent, *cn - 1));
-
-            let (curr, child_num) = self.parents.last_mut()?;
-            let dagid = *curr;
-
-            match self.tree.neighbors(*curr).get(*child_num) {
-                Some(child_id) => {
-                    let original_child_num = std::mem::replace(child_num, *child_num + 1);
-                    let unseen = self.visited.insert(*child_id);
-                    if unseen {
-                        self.parents.push((*child_id, 0));
-                    }
-                    if order == TraversalOrder::PreOrder && unseen && original_child_num == 0 {
-                        return Some(DFOut {
-                            depth,
-                            parent_ref,
-                            dagid,
-                        });
-                    }
+        loop {
+            let frame = self.stack.last_mut()?;
+            let emit = match self.order {
+                TraversalOrder::PreOrder => true,
+                TraversalOrder::InOrder => frame.explored,
+                TraversalOrder::PostOrder => false,
+            };
+            if emit && !frame.emitted {
+                frame.emitted = true;
+                return Some(frame.out);
+            }
+
+            let visited = &mut self.visited;
+            match frame.children.find(|&(_, c)| !visited[c]) {
+                Some((i, child)) => {
+                    visited[child] = true;
+                    frame.explored = true;
+                    let child = DFOut {
+                        dagid: child,
+                        depth: frame.out.depth + 1,
+                        generation: frame.out.generation + 1,
+                        parent_ref: Some((frame.out.dagid, i)),
+                    };
+                    self.push(child);
                 }
                 None => {
-                    let original_child_num = self.parents.pop().unwrap().1;
-                    if order == TraversalOrder::PostOrder {
-                        return Some(DFOut {
-                            depth,
-                            parent_ref,
-                            dagid,
-                        });
-                    } else if original_child_num == 0 {
-                        return Some(DFOut {
-                            depth,
-                            parent_ref,
-                            dagid,
-                        });
+                    let frame = self.stack.pop().unwrap();
+                    if !frame.emitted {
+                        return Some(frame.out);
                     }
                 }
             }
         }
-        None
     }
 }
 
+#[cfg(test)]
+fn dfs_ids<G: Graph>(g: &G, from: DAGID, order: TraversalOrder) -> Vec<DAGID> {
+    g.depth_first_iter(from, order).map(|v| v.dagid).collect()
+}
+
 #[test]
 fn test_linked_list() {
     let pairs = [(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 6)];
     let dag = DAG::from_pairs(pairs);
-    let iter = dag.depth_first_iter::<{ TraversalOrder::PreOrder }>(0);
+    let iter = dag.depth_first_iter(0, TraversalOrder::PreOrder);
     for (i, v) in iter.enumerate() {
         assert_eq!(i, v.depth);
         assert_eq!(i, v.dagid);
+        assert_eq!(v.parent_ref, i.checked_sub(1).map(|p| (p, 0)));
     }
 }
 
@@ -252,14 +513,108 @@ fn test_linked_list() {
 fn test_simple_tree() {
     let pairs = [(0, 1), (1, 2), (1, 3), (3, 4), (3, 5), (5, 6)];
     let dag = DAG::from_pairs(pairs);
-    let iter = dag.depth_first_iter::<{ TraversalOrder::PreOrder }>(0);
-    for _v in iter {}
+    use TraversalOrder::*;
+    assert_eq!(dfs_ids(&dag, 0, PreOrder), [0, 1, 2, 3, 4, 5, 6]);
+    assert_eq!(dfs_ids(&dag, 0, PostOrder), [2, 4, 6, 5, 3, 1, 0]);
+    assert_eq!(dfs_ids(&dag, 0, InOrder), [2, 1, 4, 3, 6, 5, 0]);
+
+    let mut visited = vec![];
+    dag.depth_first_visit(0, crate::Direction::Outgoing, |v| visited.push(v));
+    let pre: Vec<_> = dag.depth_first_iter(0, PreOrder).collect();
+    assert_eq!(pre, visited);
+    for order in [PostOrder, InOrder] {
+        let mut out: Vec<_> = dag.depth_first_iter(0, order).collect();
+        out.sort_by_key(|v| v.dagid);
+        assert_eq!(out, pre);
+    }
 }
 
 #[test]
-fn test_small_cycle() {
-    let pairs = [(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 6), (6, 0)];
+fn test_attributes() {
+    use crate::attrs::Value;
+    let mut dag = DAG::from_pairs([(0, 1), (1, 2), (0, 2)]);
+    let e = dag.insert_edge(0, 1).unwrap();
+    assert_eq!((e, dag.edge(e)), (3, (0, 1)));
+    assert_eq!(dag.out_edges(0), [0, 2, 3]);
+    assert_eq!(dag.in_edges(2), [1, 2]);
+    for (e, w) in [(1, 4.), (2, 2.5), (3, 0.5)] {
+        dag.edge_attrs_mut()
+            .set("weight", e, Value::Float(w))
+            .unwrap();
+    }
+    assert_eq!(dag.edge_lengths("weight").unwrap(), [1., 4., 2.5, 0.5]);
+    assert_eq!(dag.edge_lengths("missing"), None);
+
+    dag.node_attrs_mut()
+        .set("label", 0, Value::Str("root".into()))
+        .unwrap();
+    let v = dag.insert(3);
+    assert_eq!(dag.node_attrs().len(), 4);
+    assert_eq!(dag.map(|&n| n * 2).node_attrs().get("label", v), None);
+    assert_eq!(dag.edge_attrs().len(), dag.num_edges());
+}
+
+#[test]
+fn test_edge_policy() {
+    let pairs = [(0, 1), (1, 1), (0, 1), (1, 0)];
     let dag = DAG::from_pairs(pairs);
-    let iter = dag.depth_first_iter::<{ TraversalOrder::PreOrder }>(0);
-    for _v in iter {}
+    assert_eq!(dag.num_edges(), 4);
+    assert_eq!(dag.find_edge(0, 1), Some(0));
+    assert!(dag.has_edge(1, 1) && !dag.has_edge(1, 2));
+
+    let mut dag = DAG::from_pairs_with(pairs, EdgePolicy::Dedupe).unwrap();
+    assert_eq!(dag.num_edges(), 2);
+    assert_eq!(dag.neighbors(0), [1]);
+    assert_eq!(dag.neighbors(1), [0]);
+    assert_eq!(dag.find_edge(1, 0), Some(1));
+    assert!(!dag.has_edge(1, 1));
+    assert_eq!(dag.insert_edge(1, 0), None);
+
+    let err = DAG::from_pairs_with(pairs, EdgePolicy::Reject).unwrap_err();
+    assert_eq!(err, EdgeError::SelfLoop(1));
+    let mut dag = DAG::with_edge_policy(EdgePolicy::Reject);
+    let (a, b) = (dag.insert('a'), dag.insert('b'));
+    assert_eq!(dag.try_insert_edge(a, b), Ok(Some(0)));
+    assert_eq!(dag.try_insert_edge(a, b), Err(EdgeError::Duplicate(a, b)));
+    // The policy doesn't take part in comparisons.
+    assert_eq!(dag, DAG::from_pairs([('a', 'b')]));
+}
+
+#[test]
+fn test_shared_children_and_cycles() {
+    use TraversalOrder::*;
+    let diamond = DAG::from_pairs([(0, 1), (0, 2), (1, 3), (2, 3)]);
+    let pre: Vec<_> = diamond.depth_first_iter(0, PreOrder).collect();
+    assert_eq!(
+        pre.iter().map(|v| v.dagid).collect::<Vec<_>>(),
+        [0, 1, 3, 2]
+    );
+    assert_eq!((pre[2].depth, pre[2].parent_ref), (2, Some((1, 0))));
+    assert_eq!((pre[3].depth, pre[3].parent_ref), (1, Some((0, 1))));
+    assert_eq!(dfs_ids(&diamond, 0, PostOrder), [3, 1, 2, 0]);
+
+    let pairs = [(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 6), (6, 0)];
+    let cycle = DAG::from_pairs(pairs);
+    assert_eq!(dfs_ids(&cycle, 0, PreOrder), [0, 1, 2, 3, 4, 5, 6]);
+    assert_eq!(dfs_ids(&cycle, 3, PostOrder), [2, 1, 0, 6, 5, 4, 3]);
+    assert_eq!(dfs_ids(&cycle, 0, InOrder), [6, 5, 4, 3, 2, 1, 0]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    use crate::attrs::Value;
+    let mut dag = DAG::from_pairs([(3, 4), (4, 5), (3, 5)]);
+    dag.edge_attrs_mut().set("w", 2, Value::Int(7)).unwrap();
+    let s = serde_json::to_string(&dag).unwrap();
+    assert!(
+        s.starts_with(r#"{"nodes":[3,4,5],"edges":[[0,1],[1,2],[0,2]]"#),
+        "{s}"
+    );
+    assert_eq!(serde_json::from_str::<DAG<i32>>(&s).unwrap(), dag);
+
+    let bad = r#"{"nodes": [1, 2], "edges": [[0, 2]]}"#;
+    assert!(serde_json::from_str::<DAG<i32>>(bad).is_err());
+    let bad = r#"{"nodes": [1], "edges": [], "node_attrs": {"len": 3, "columns": {}}}"#;
+    assert!(serde_json::from_str::<DAG<i32>>(bad).is_err());
 }
@@ -0,0 +1,225 @@
+//! Embedding of general graphs in the Poincare disc by optimization, so that structure other
+//! than a spanning tree, such as cycles and shared descendants, is reflected in the layout.
+
+use super::dag::{DFOut, DAGID};
+use super::generate::Rng;
+use super::graph::{Direction, Graph};
+use super::poincare_ball::PoincarePoint;
+use super::{hyperbolic_project, FP};
+
+/// Largest norm of an embedded point, keeping it strictly inside the disc.
+const MAX_NORM: FP = 1. - 1e-6;
+
+/// Options for [`hyperbolic_embed`].
+#[derive(Debug, Clone, Copy)]
+pub struct EmbedOptions {
+    /// Number of passes over all pairs of nodes being fitted.
+    pub epochs: usize,
+    /// Number of randomly chosen nodes whose distance to every other node is fitted, along with
+    /// the distance between the ends of each edge. Fitting all pairs is slower but more
+    /// accurate.
+    pub pivots: usize,
+    /// Hyperbolic distance between nodes one edge apart.
+    pub scale: FP,
+    pub seed: u64,
+}
+
+impl Default for EmbedOptions {
+    fn default() -> Self {
+        Self {
+            epochs: 30,
+            pivots: 32,
+            scale: 1.,
+            seed: 0,
+        }
+    }
+}
+
+/// Places every node reachable from `focus` in the Poincare disc so that hyperbolic distances
+/// match distances in the graph with edges undirected, with `focus` at the center. Returns the
+/// same as [`hyperbolic_project`], whose layout is used as a starting point.
+///
+/// Minimizes the stress `Σ ((d(x_i, x_j) - t_ij) / t_ij)²` over pairs of adjacent nodes and of
+/// nodes and pivots by Riemannian SGD, stepping nodes along the geodesic that corrects the
+/// distance of one pair at a time with a decaying step size.
+pub fn hyperbolic_embed<G: Graph>(
+    g: &G,
+    focus: DAGID,
+    opts: &EmbedOptions,
+) -> (Vec<[FP; 2]>, impl Iterator<Item = DFOut>) {
+    let (projected, info) = hyperbolic_project(g, focus);
+    let info: Vec<_> = info.collect();
+    let mut rng = Rng::new(opts.seed);
+
+    // Start from the projection's directions, at the distance of each node's depth.
+    let mut points = vec![PoincarePoint::zero(); g.num_nodes()];
+    for v in &info {
+        let [x, y] = projected[v.dagid];
+        let norm = x.hypot(y);
+        if norm > 0. {
+            let r = (opts.scale * v.depth as FP / 2.).tanh().min(MAX_NORM);
+            points[v.dagid] = PoincarePoint([x * r / norm, y * r / norm]);
+        }
+    }
+
+    let pairs = pairs(g, &info, opts, &mut rng);
+    if !pairs.is_empty() {
+        let weights = pairs.iter().map(|p| (p.target * p.target).recip());
+        let (w_min, w_max) = weights.fold((FP::MAX, FP::MIN_POSITIVE), |(lo, hi), w| {
+            (lo.min(w), hi.max(w))
+        });
+        let (eta_max, eta_min) = (1. / w_min, 0.01 / w_max);
+        let decay = (eta_min / eta_max).ln() / (opts.epochs.max(2) - 1) as FP;
+        let mut order: Vec<_> = (0..pairs.len()).collect();
+        for epoch in 0..opts.epochs {
+            let eta = eta_max * (decay * epoch as FP).exp();
+            for i in (1..order.len()).rev() {
+                order.swap(i, rng.below(i + 1));
+            }
+            for &i in &order {
+                let p = &pairs[i];
+                let step = (eta / (p.target * p.target)).min(1.);
+                p.fit(&mut points, step);
+            }
+        }
+    }
+
+    // Move the focus back to the center, which is an isometry of the disc.
+    let center = points[focus].neg();
+    let mut coords = vec![[0.; 2]; g.num_nodes()];
+    for v in &info {
+        coords[v.dagid] = center.mobius_add(&points[v.dagid]).0;
+    }
+    (coords, info.into_iter())
+}
+
+/// Two nodes whose distance is fitted to `target`.
+struct Pair {
+    a: DAGID,
+    b: DAGID,
+    target: FP,
+    /// Whether `a` is a pivot, which is left in place so it stays a stable reference.
+    pivot: bool,
+}
+
+impl Pair {
+    /// Moves the ends towards or away from each other along the geodesic between them, by
+    /// `step` of the difference between their distance and the target.
+    fn fit(&self, points: &mut [PoincarePoint], step: FP) {
+        let (x, y) = (points[self.a], points[self.b]);
+        let d = x.dist(&y);
+        if d.is_nan() || d <= 1e-9 {
+            return;
+        }
+        let shift = step * (d - self.target);
+        if self.pivot {
+            points[self.b] = move_towards(&y, &x, d, shift);
+        } else {
+            points[self.a] = move_towards(&x, &y, d, shift / 2.);
+            points[self.b] = move_towards(&y, &x, d, shift / 2.);
+        }
+    }
+}
+
+/// The ends of each edge between reachable nodes, and each reachable node with each pivot.
+fn pairs<G: Graph>(g: &G, info: &[DFOut], opts: &EmbedOptions, rng: &mut Rng) -> Vec<Pair> {
+    let mut reached = vec![false; g.num_nodes()];
+    for v in info {
+        reached[v.dagid] = true;
+    }
+    let mut out = vec![];
+    for v in info {
+        for u in g.neighbors(v.dagid).filter(|&u| u != v.dagid && reached[u]) {
+            out.push(Pair {
+                a: v.dagid,
+                b: u,
+                target: opts.scale,
+                pivot: false,
+            });
+        }
+    }
+    let mut nodes: Vec<_> = info.iter().map(|v| v.dagid).collect();
+    let pivots = opts.pivots.min(nodes.len());
+    for i in 0..pivots {
+        let j = i + rng.below(nodes.len() - i);
+        nodes.swap(i, j);
+    }
+    for &pivot in &nodes[..pivots] {
+        g.breadth_first_visit(pivot, Direction::Both, |v| {
+            if v.depth > 0 {
+                out.push(Pair {
+                    a: pivot,
+                    b: v.dagid,
+                    target: opts.scale * v.depth as FP,
+                    pivot: true,
+                });
+            }
+        });
+    }
+    out
+}
+
+/// Moves `x` a hyperbolic distance `by` towards `y`, which is `d` away, or away from it if `by`
+/// is negative, along the geodesic through them.
+fn move_towards(x: &PoincarePoint, y: &PoincarePoint, d: FP, by: FP) -> PoincarePoint {
+    // Translated so that `x` is at the origin, the geodesic is the line through `(-x) ⊕ y`, whose
+    // log has length `d / 2` under this crate's convention. Scaling it to `by / 2` and mapping
+    // back steps the right distance, as in `PoincarePoint::geodesic`.
+    let dir = x.neg().mobius_add(y).log();
+    let moved = x.mobius_add(&PoincarePoint::exp(&dir.map(|v| v * by / d)));
+    let norm = moved.0[0].hypot(moved.0[1]);
+    if norm > MAX_NORM {
+        PoincarePoint(moved.0.map(|c| c * MAX_NORM / norm))
+    } else {
+        moved
+    }
+}
+
+/// Mean relative error between the hyperbolic distance of each pair of nodes reachable from
+/// each other and `scale` times their distance in the graph with edges undirected. Takes time
+/// quadratic in the number of nodes.
+pub fn distortion<G: Graph>(g: &G, coords: &[[FP; 2]], scale: FP) -> FP {
+    let (mut total, mut count) = (0., 0);
+    for a in 0..g.num_nodes() {
+        let pa = PoincarePoint::from_raw(&coords[a]);
+        g.breadth_first_visit(a, Direction::Both, |v| {
+            if v.depth > 0 {
+                let target = scale * v.depth as FP;
+                let d = pa.dist(&PoincarePoint::from_raw(&coords[v.dagid]));
+                total += (d - target).abs() / target;
+                count += 1;
+            }
+        });
+    }
+    total / count.max(1) as FP
+}
+
+#[test]
+fn test_embed_cycle() {
+    use crate::DAG;
+    // A 12-cycle, whose opposite nodes are 6 apart but 11 apart along its spanning tree.
+    let ring = DAG::from_pairs((0..12).map(|i| (i, (i + 1) % 12)));
+    let (projected, _) = hyperbolic_project(&ring, 0);
+    let opts = EmbedOptions::default();
+    let (coords, info) = hyperbolic_embed(&ring, 0, &opts);
+    assert_eq!(info.count(), 12);
+    assert_eq!(coords[0], [0.; 2]);
+    assert!(coords.iter().all(|&[x, y]| x.hypot(y) < 1.));
+    let before = distortion(&ring, &projected, opts.scale);
+    let after = distortion(&ring, &coords, opts.scale);
+    assert!(after < 0.25 && after < before, "{before} -> {after}");
+
+    let dag = crate::generate::citation_dag(200, 3, 1);
+    let (projected, _) = hyperbolic_project(&dag, 0);
+    let (coords, _) = hyperbolic_embed(&dag, 0, &opts);
+    assert!(distortion(&dag, &coords, 1.) < distortion(&dag, &projected, 1.));
+
+    // Steps stay on the geodesic and cover the distance asked for.
+    let (x, y) = (PoincarePoint([0.3, -0.2]), PoincarePoint([-0.5, 0.4]));
+    let d = x.dist(&y);
+    let moved = move_towards(&x, &y, d, 0.5);
+    assert!((x.dist(&moved) - 0.5).abs() < 1e-4);
+    assert!((moved.dist(&y) - (d - 0.5)).abs() < 1e-4);
+    let moved = move_towards(&x, &y, d, -0.5);
+    assert!((moved.dist(&y) - (d + 0.5)).abs() < 1e-4);
+}
@@ -0,0 +1,146 @@
+//! Seeded synthetic graph generators, for tests and demos that shouldn't depend on external
+//! datasets. Each node's payload is its own `DAGID`.
+
+use super::dag::{DAG, DAGID};
+use super::FP;
+
+/// Small deterministic PRNG (SplitMix64), so generated graphs are stable across platforms.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    /// Uniform integer in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert_ne!(n, 0);
+        (self.next_u64() % n as u64) as usize
+    }
+    /// Uniform float in `[0, 1)`.
+    pub fn next_fp(&mut self) -> FP {
+        (self.next_u64() >> 11) as FP / (1u64 << 53) as FP
+    }
+}
+
+fn with_nodes(n: usize) -> DAG<usize> {
+    let mut dag = DAG::new();
+    for i in 0..n {
+        dag.insert(i);
+    }
+    dag
+}
+
+/// Complete tree where every internal node has `k` children, with leaves at `depth`.
+pub fn k_ary_tree(k: usize, depth: usize) -> DAG<usize> {
+    let mut dag = with_nodes(1);
+    let mut frontier = vec![0];
+    for _ in 0..depth {
+        let mut next = vec![];
+        for parent in frontier {
+            for _ in 0..k {
+                let child = dag.insert(dag.num_nodes());
+                dag.insert_edge(parent, child);
+                next.push(child);
+            }
+        }
+        frontier = next;
+    }
+    dag
+}
+
+/// Uniform random recursive tree: each node attaches to a uniformly chosen earlier node.
+pub fn random_tree(n: usize, seed: u64) -> DAG<usize> {
+    let mut rng = Rng::new(seed);
+    let mut dag = with_nodes(n);
+    for i in 1..n {
+        dag.insert_edge(rng.below(i), i);
+    }
+    dag
+}
+
+/// Citation-like DAG: each new node references up to `refs` distinct earlier nodes, preferring
+/// those which are already highly cited. Edges point from the citing node to the cited one.
+pub fn citation_dag(n: usize, refs: usize, seed: u64) -> DAG<usize> {
+    let mut rng = Rng::new(seed);
+    let mut dag = with_nodes(n);
+    // Every node appears once, plus once per citation it has received.
+    let mut pool: Vec<DAGID> = vec![];
+    let mut cited = vec![];
+    for i in 0..n {
+        cited.clear();
+        while cited.len() < refs.min(i) {
+            let dst = pool[rng.below(pool.len())];
+            if !cited.contains(&dst) {
+                cited.push(dst);
+            }
+        }
+        for &dst in &cited {
+            dag.insert_edge(i, dst);
+            pool.push(dst);
+        }
+        pool.push(i);
+    }
+    dag
+}
+
+/// Barabási–Albert scale-free graph: each new node links to `m` distinct earlier nodes chosen
+/// proportionally to their degree. Starts from a clique of `m + 1` nodes.
+pub fn scale_free(n: usize, m: usize, seed: u64) -> DAG<usize> {
+    let mut rng = Rng::new(seed);
+    let mut dag = with_nodes(n);
+    // Each node appears once per incident edge.
+    let mut pool: Vec<DAGID> = vec![];
+    let seed_size = (m + 1).min(n);
+    for i in 0..seed_size {
+        for j in 0..i {
+            dag.insert_edge(i, j);
+            pool.extend([i, j]);
+        }
+    }
+    let mut targets = vec![];
+    for i in seed_size..n {
+        targets.clear();
+        while targets.len() < m {
+            let dst = pool[rng.below(pool.len())];
+            if !targets.contains(&dst) {
+                targets.push(dst);
+            }
+        }
+        for &dst in &targets {
+            dag.insert_edge(i, dst);
+            pool.extend([i, dst]);
+        }
+    }
+    dag
+}
+
+#[test]
+fn test_k_ary_tree() {
+    let dag = k_ary_tree(3, 4);
+    assert_eq!(dag.num_nodes(), 1 + 3 + 9 + 27 + 81);
+    assert_eq!(dag.num_edges(), dag.num_nodes() - 1);
+    assert_eq!(dag.neighbors(0), &[1, 2, 3]);
+}
+
+#[test]
+fn test_seeded_generators() {
+    assert_eq!(random_tree(100, 7), random_tree(100, 7));
+    assert_ne!(random_tree(100, 7), random_tree(100, 8));
+
+    let cit = citation_dag(500, 3, 1);
+    assert_eq!(cit.num_edges(), 1 + 2 + 3 * 497);
+    for i in 0..cit.num_nodes() {
+        assert!(cit.neighbors(i).iter().all(|&dst| dst < i));
+    }
+
+    let sf = scale_free(500, 2, 1);
+    assert_eq!(sf.num_edges(), 3 + 2 * 497);
+}
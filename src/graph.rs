@@ -0,0 +1,257 @@
+use super::dag::{DFOut, DepthFirstIter, EdgeID, TraversalOrder, DAGID};
+use std::collections::{HashSet, VecDeque};
+
+/// Read-only access to a graph's adjacency and node data.
+///
+/// Implemented by [`DAG`](crate::DAG) and [`CsrDag`](crate::CsrDag), and can be implemented for
+/// other adjacency sources (e.g. a petgraph graph) so they can be traversed and projected without
+/// first being copied into a `DAG`.
+pub trait Graph {
+    type Node;
+    type Neighbors<'a>: Iterator<Item = DAGID> + 'a
+    where
+        Self: 'a;
+
+    /// Number of node slots: every valid DAGID is below this.
+    fn num_nodes(&self) -> usize;
+    /// All vertices `v` such that `of` -> `v`.
+    fn neighbors(&self, of: DAGID) -> Self::Neighbors<'_>;
+    /// All vertices `v` such that `v` -> `to`.
+    fn incoming(&self, to: DAGID) -> Self::Neighbors<'_>;
+    /// Data for a node, or `None` if there is no node with that ID.
+    fn node(&self, id: DAGID) -> Option<&Self::Node>;
+    /// An edge from `from` to `to`, for looking up per-edge data such as lengths. `None` if there
+    /// is no such edge or the graph doesn't number its edges.
+    fn find_edge(&self, _from: DAGID, _to: DAGID) -> Option<EdgeID> {
+        None
+    }
+
+    fn depth_first_iter(&self, from: DAGID, order: TraversalOrder) -> DepthFirstIter<'_, Self>
+    where
+        Self: Sized,
+    {
+        DepthFirstIter::new(self, from, order)
+    }
+
+    /// Visits the nodes reachable from `from` in `dir` depth first, each once.
+    fn depth_first_visit(&self, from: DAGID, dir: Direction, mut f: impl FnMut(DFOut))
+    where
+        Self: Sized,
+    {
+        let mut work = vec![DFOut::root(from)];
+        let mut seen = HashSet::new();
+
+        while let Some(node) = work.pop() {
+            if !seen.insert(node.dagid) {
+                continue;
+            }
+            let start = work.len();
+            work.extend(steps(self, &node, dir));
+            work[start..].reverse();
+            f(node);
+        }
+    }
+
+    /// Visits the nodes reachable from `from` in `dir` breadth first, each once.
+    fn breadth_first_visit(&self, from: DAGID, dir: Direction, mut f: impl FnMut(DFOut))
+    where
+        Self: Sized,
+    {
+        let mut work = VecDeque::new();
+        work.push_back(DFOut::root(from));
+        let mut seen = HashSet::new();
+
+        while let Some(node) = work.pop_front() {
+            if !seen.insert(node.dagid) {
+                continue;
+            }
+            work.extend(steps(self, &node, dir));
+            f(node);
+        }
+    }
+
+    /// The nodes along a shortest path from `from` to `to` following edges in `dir`, including
+    /// both ends, or `None` if `to` can't be reached.
+    fn shortest_path(&self, from: DAGID, to: DAGID, dir: Direction) -> Option<Vec<DAGID>>
+    where
+        Self: Sized,
+    {
+        let mut prev = vec![None; self.num_nodes()];
+        prev[from] = Some(from);
+        let mut work = VecDeque::from([from]);
+        while let Some(curr) = work.pop_front() {
+            if curr == to {
+                let mut path = vec![to];
+                let mut node = to;
+                while node != from {
+                    node = prev[node].unwrap();
+                    path.push(node);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            let children = dir.outgoing().then(|| self.neighbors(curr));
+            let parents = dir.incoming().then(|| self.incoming(curr));
+            for next in children
+                .into_iter()
+                .flatten()
+                .chain(parents.into_iter().flatten())
+            {
+                if prev[next].is_none() {
+                    prev[next] = Some(curr);
+                    work.push_back(next);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// The nodes one edge away from `node` in `dir`, children before parents. Each is numbered by
+/// its position among them, so parents are numbered after all children.
+fn steps<'a, G: Graph>(g: &'a G, node: &DFOut, dir: Direction) -> impl Iterator<Item = DFOut> + 'a {
+    let &DFOut {
+        dagid,
+        depth,
+        generation,
+        ..
+    } = node;
+    let step = move |generation, first: usize| {
+        move |(i, next)| DFOut {
+            dagid: next,
+            depth: depth + 1,
+            generation,
+            parent_ref: Some((dagid, first + i)),
+        }
+    };
+    let children = dir
+        .outgoing()
+        .then(|| g.neighbors(dagid).enumerate().map(step(generation + 1, 0)));
+    let num_children = if dir.outgoing() {
+        g.neighbors(dagid).count()
+    } else {
+        0
+    };
+    let parents = dir.incoming().then(|| {
+        g.incoming(dagid)
+            .enumerate()
+            .map(step(generation - 1, num_children))
+    });
+    children
+        .into_iter()
+        .flatten()
+        .chain(parents.into_iter().flatten())
+}
+
+/// Which edges a traversal follows.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    /// From parents to children, reaching descendants.
+    Outgoing,
+    /// From children to parents, reaching ancestors.
+    Incoming,
+    /// Either way, reaching every connected node.
+    #[default]
+    Both,
+}
+
+impl Direction {
+    pub fn outgoing(self) -> bool {
+        self != Direction::Incoming
+    }
+    pub fn incoming(self) -> bool {
+        self != Direction::Outgoing
+    }
+}
+
+/// An implicit complete binary tree, standing in for a user-provided adapter.
+#[cfg(test)]
+struct ImplicitBinaryTree(usize);
+
+#[cfg(test)]
+impl Graph for ImplicitBinaryTree {
+    type Node = ();
+    type Neighbors<'a> = std::vec::IntoIter<DAGID>;
+    fn num_nodes(&self) -> usize {
+        self.0
+    }
+    fn neighbors(&self, of: DAGID) -> Self::Neighbors<'_> {
+        let n = self.0;
+        let v: Vec<_> = [2 * of + 1, 2 * of + 2]
+            .into_iter()
+            .filter(|&c| c < n)
+            .collect();
+        v.into_iter()
+    }
+    fn incoming(&self, to: DAGID) -> Self::Neighbors<'_> {
+        let v: Vec<_> = (to > 0).then(|| (to - 1) / 2).into_iter().collect();
+        v.into_iter()
+    }
+    fn node(&self, id: DAGID) -> Option<&()> {
+        (id < self.0).then_some(&())
+    }
+}
+
+#[test]
+fn test_custom_graph() {
+    let g = ImplicitBinaryTree(15);
+    let dag = crate::DAG::from_pairs((1..15).map(|i| ((i - 1) / 2, i)));
+    let (mut a, mut b) = (vec![], vec![]);
+    g.breadth_first_visit(3, Direction::Both, |d| a.push(d));
+    dag.breadth_first_visit(3, Direction::Both, |d| b.push(d));
+    assert_eq!(a, b);
+
+    let (coords, _) = crate::hyperbolic_project(&g, 0);
+    assert_eq!(coords, crate::hyperbolic_project(&dag, 0).0);
+}
+
+#[test]
+fn test_direction() {
+    // 0 -> 1 -> 2 -> 3, and 4 -> 2.
+    let dag = crate::DAG::from_pairs([(0, 1), (1, 2), (2, 3), (4, 2)]);
+    let visit = |dir| {
+        let mut out = vec![];
+        dag.breadth_first_visit(2, dir, |d| out.push((d.dagid, d.generation)));
+        out
+    };
+    assert_eq!(visit(Direction::Outgoing), [(2, 0), (3, 1)]);
+    assert_eq!(
+        visit(Direction::Incoming),
+        [(2, 0), (1, -1), (4, -1), (0, -2)]
+    );
+    assert_eq!(
+        visit(Direction::Both),
+        [(2, 0), (3, 1), (1, -1), (4, -1), (0, -2)]
+    );
+
+    let mut out = vec![];
+    dag.depth_first_visit(3, Direction::Incoming, |d| out.push((d.dagid, d.depth)));
+    assert_eq!(out, [(3, 0), (2, 1), (1, 2), (0, 3), (4, 2)]);
+
+    // Parents are numbered after children, so no two nodes share a parent_ref.
+    let dag = crate::DAG::from_pairs([(0, 1), (2, 1), (1, 3), (1, 4)]);
+    let mut refs = vec![];
+    dag.breadth_first_visit(3, Direction::Both, |d| refs.extend(d.parent_ref));
+    assert_eq!(refs, [(3, 0), (1, 1), (1, 2), (1, 3)]);
+}
+
+#[test]
+fn test_shortest_path() {
+    // 0 -> 1 -> 2 -> 3, 0 -> 3 and 4 -> 2.
+    let dag = crate::DAG::from_pairs([(0, 1), (1, 2), (2, 3), (0, 3), (4, 2)]);
+    assert_eq!(
+        dag.shortest_path(0, 3, Direction::Outgoing),
+        Some(vec![0, 3])
+    );
+    assert_eq!(dag.shortest_path(1, 1, Direction::Outgoing), Some(vec![1]));
+    assert_eq!(dag.shortest_path(1, 4, Direction::Outgoing), None);
+    assert_eq!(
+        dag.shortest_path(1, 4, Direction::Both),
+        Some(vec![1, 2, 4])
+    );
+    assert_eq!(
+        dag.shortest_path(3, 0, Direction::Incoming),
+        Some(vec![3, 0])
+    );
+}
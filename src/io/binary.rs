@@ -0,0 +1,410 @@
+//! Compact binary graph format, with an optional precomputed layout, so that large graphs can
+//! be loaded without re-parsing or re-projecting them.
+//!
+//! All integers are little-endian `u32`s and floats `f64`s:
+//!
+//! ```text
+//! magic "HYPB", version, flags (bit 0: has layout), num_nodes, num_edges
+//! offsets: [u32; num_nodes + 1], targets: [u32; num_edges]     outgoing edges as CSR
+//! payload kind (0: none, 1: u32, 2: utf8), then unless none:
+//!   offsets: [u32; num_nodes + 1], bytes, zero padded to 4 bytes
+//! if has layout:
+//!   focus, coords: [f64; 2 * num_nodes],
+//!   num_placed, then per placed node: dagid, depth, generation (as i32), parent, child index
+//!   (u32::MAX if root)
+//! ```
+//!
+//! [`BinaryGraph`] reads directly out of the borrowed bytes without copying the adjacency, after
+//! checking once that every offset and ID in them is in range.
+
+use super::Error;
+use crate::csr::CsrDag;
+use crate::dag::{DFOut, DAG, DAGID};
+use crate::{Layout, FP};
+use std::io::{self, Write};
+use std::ops::Range;
+
+pub const MAGIC: &[u8; 4] = b"HYPB";
+pub const VERSION: u32 = 2;
+
+const HAS_LAYOUT: u32 = 1;
+const NONE: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadKind {
+    None = 0,
+    U32 = 1,
+    Utf8 = 2,
+}
+
+/// Node data which can be stored in the binary format.
+pub trait BinaryPayload {
+    const KIND: PayloadKind;
+    fn encode(&self, out: &mut Vec<u8>);
+}
+
+impl BinaryPayload for u32 {
+    const KIND: PayloadKind = PayloadKind::U32;
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend(self.to_le_bytes());
+    }
+}
+
+impl BinaryPayload for String {
+    const KIND: PayloadKind = PayloadKind::Utf8;
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend(self.as_bytes());
+    }
+}
+
+impl BinaryPayload for () {
+    const KIND: PayloadKind = PayloadKind::None;
+    fn encode(&self, _: &mut Vec<u8>) {}
+}
+
+fn push(out: &mut Vec<u8>, v: usize) {
+    out.extend(
+        u32::try_from(v)
+            .expect("too large for binary format")
+            .to_le_bytes(),
+    );
+}
+
+/// Writes a graph and optionally its layout.
+pub fn write<T: BinaryPayload>(
+    mut w: impl Write,
+    dag: &DAG<T>,
+    layout: Option<&Layout>,
+) -> io::Result<()> {
+    let n = dag.num_nodes();
+    let mut out = MAGIC.to_vec();
+    push(&mut out, VERSION as usize);
+    push(
+        &mut out,
+        if layout.is_some() { HAS_LAYOUT } else { 0 } as usize,
+    );
+    push(&mut out, n);
+    push(&mut out, dag.num_edges());
+
+    let mut offset = 0;
+    push(&mut out, 0);
+    for i in 0..n {
+        offset += dag.neighbors(i).len();
+        push(&mut out, offset);
+    }
+    for i in 0..n {
+        dag.neighbors(i).iter().for_each(|&j| push(&mut out, j));
+    }
+
+    push(&mut out, T::KIND as usize);
+    if T::KIND != PayloadKind::None {
+        let mut bytes = vec![];
+        push(&mut out, 0);
+        for i in 0..n {
+            dag.get(i).encode(&mut bytes);
+            push(&mut out, bytes.len());
+        }
+        bytes.resize(bytes.len().next_multiple_of(4), 0);
+        out.extend(bytes);
+    }
+
+    if let Some(layout) = layout {
+        push(&mut out, layout.focus);
+        for c in layout.coords.iter().flatten() {
+            out.extend(c.to_le_bytes());
+        }
+        push(&mut out, layout.info.len());
+        for dfout in &layout.info {
+            push(&mut out, dfout.dagid);
+            push(&mut out, dfout.depth);
+            out.extend((dfout.generation as i32).to_le_bytes());
+            let (parent, child) = dfout.parent_ref.unwrap_or((NONE as usize, NONE as usize));
+            push(&mut out, parent);
+            push(&mut out, child);
+        }
+    }
+    w.write_all(&out)
+}
+
+/// A graph in the binary format, read in place.
+#[derive(Debug, Clone, Copy)]
+pub struct BinaryGraph<'a> {
+    bytes: &'a [u8],
+    num_nodes: usize,
+    num_edges: usize,
+    offsets: usize,
+    targets: usize,
+    payload_kind: PayloadKind,
+    payload_offsets: usize,
+    payload_bytes: usize,
+    layout: Option<usize>,
+}
+
+fn corrupt(offset: usize, msg: impl Into<String>) -> Error {
+    Error::Corrupt {
+        offset,
+        msg: msg.into(),
+    }
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+impl<'a> BinaryGraph<'a> {
+    /// Validates the header and section bounds of `bytes`.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, Error> {
+        let mut at: usize = 0;
+        // Lengths are `None` if computing them overflowed, which can only mean corrupt data.
+        let mut take = |len: Option<usize>| {
+            let start = at;
+            match len.and_then(|len| at.checked_add(len)) {
+                Some(end) if end <= bytes.len() => {
+                    at = end;
+                    Ok(start)
+                }
+                _ => Err(corrupt(start, "unexpected end of data")),
+            }
+        };
+        if &bytes[take(Some(4))?..4] != MAGIC {
+            return Err(corrupt(0, "not a hyperbol binary graph"));
+        }
+        let version = u32_at(bytes, take(Some(4))?);
+        if version != VERSION {
+            return Err(corrupt(4, format!("unsupported version {version}")));
+        }
+        let flags = u32_at(bytes, take(Some(4))?);
+        let num_nodes = u32_at(bytes, take(Some(4))?) as usize;
+        let num_edges = u32_at(bytes, take(Some(4))?) as usize;
+        let per_node = |width: usize| num_nodes.checked_add(1)?.checked_mul(width);
+        let offsets = take(per_node(4))?;
+        let targets = take(num_edges.checked_mul(4))?;
+        let kind_at = take(Some(4))?;
+        let payload_kind = match u32_at(bytes, kind_at) {
+            0 => PayloadKind::None,
+            1 => PayloadKind::U32,
+            2 => PayloadKind::Utf8,
+            k => return Err(corrupt(kind_at, format!("unknown payload kind {k}"))),
+        };
+        let (mut payload_offsets, mut payload_bytes) = (0, 0);
+        if payload_kind != PayloadKind::None {
+            payload_offsets = take(per_node(4))?;
+            let offset = |i: usize| u32_at(bytes, payload_offsets + 4 * i) as usize;
+            let len = offset(num_nodes);
+            payload_bytes = take(len.checked_next_multiple_of(4))?;
+            if let Some(bad) = (0..num_nodes).find(|&i| offset(i) > offset(i + 1)) {
+                return Err(corrupt(
+                    payload_offsets + 4 * bad,
+                    "payload offsets out of order",
+                ));
+            }
+            // Check payloads decode, so the typed accessors only fail on the wrong kind.
+            let payload =
+                |i: usize| &bytes[payload_bytes + offset(i)..payload_bytes + offset(i + 1)];
+            let valid = |p: &[u8]| match payload_kind {
+                PayloadKind::U32 => p.len() == 4,
+                _ => std::str::from_utf8(p).is_ok(),
+            };
+            if let Some(bad) = (0..num_nodes).find(|&i| !valid(payload(i))) {
+                return Err(corrupt(
+                    payload_offsets + 4 * bad,
+                    format!("invalid {payload_kind:?} payload for node {bad}"),
+                ));
+            }
+        }
+        let layout = if flags & HAS_LAYOUT != 0 {
+            let start = take(num_nodes.checked_mul(16).and_then(|len| len.checked_add(4)))?;
+            let placed = u32_at(bytes, take(Some(4))?) as usize;
+            let info = take(placed.checked_mul(20))?;
+            if u32_at(bytes, start) as usize >= num_nodes {
+                return Err(corrupt(start, "layout focus out of range"));
+            }
+            for i in 0..placed {
+                let at = info + 20 * i;
+                let parent = u32_at(bytes, at + 12);
+                if u32_at(bytes, at) as usize >= num_nodes
+                    || (parent != NONE && parent as usize >= num_nodes)
+                {
+                    return Err(corrupt(at, "layout node out of range"));
+                }
+            }
+            Some(start)
+        } else {
+            None
+        };
+
+        let out = Self {
+            bytes,
+            num_nodes,
+            num_edges,
+            offsets,
+            targets,
+            payload_kind,
+            payload_offsets,
+            payload_bytes,
+            layout,
+        };
+        // Check that edges stay in bounds, so accessors can't panic later.
+        for i in 0..num_nodes {
+            let Range { start, end } = out.edge_range(i);
+            if start > end || end > num_edges {
+                return Err(corrupt(offsets + 4 * i, "edge offsets out of order"));
+            }
+        }
+        if let Some(bad) =
+            (0..num_edges).find(|&e| u32_at(bytes, targets + 4 * e) as usize >= num_nodes)
+        {
+            return Err(corrupt(targets + 4 * bad, "edge target out of range"));
+        }
+        Ok(out)
+    }
+    pub fn num_nodes(&self) -> usize {
+        self.num_nodes
+    }
+    pub fn num_edges(&self) -> usize {
+        self.num_edges
+    }
+    fn edge_range(&self, i: DAGID) -> Range<usize> {
+        let at = |i| u32_at(self.bytes, self.offsets + 4 * i) as usize;
+        at(i)..at(i + 1)
+    }
+    /// All vertices `v` such that `of` -> `v`.
+    pub fn neighbors(&self, of: DAGID) -> impl Iterator<Item = DAGID> + 'a {
+        let (bytes, targets) = (self.bytes, self.targets);
+        self.edge_range(of)
+            .map(move |e| u32_at(bytes, targets + 4 * e) as usize)
+    }
+    pub fn payload_kind(&self) -> PayloadKind {
+        self.payload_kind
+    }
+    /// Raw payload bytes of a node.
+    pub fn payload(&self, id: DAGID) -> &'a [u8] {
+        if self.payload_kind == PayloadKind::None {
+            return &[];
+        }
+        let at = |i| u32_at(self.bytes, self.payload_offsets + 4 * i) as usize;
+        &self.bytes[self.payload_bytes + at(id)..self.payload_bytes + at(id + 1)]
+    }
+    pub fn payload_u32(&self, id: DAGID) -> Option<u32> {
+        let p = self.payload(id);
+        (self.payload_kind == PayloadKind::U32 && p.len() == 4).then(|| u32_at(p, 0))
+    }
+    pub fn payload_str(&self, id: DAGID) -> Option<&'a str> {
+        (self.payload_kind == PayloadKind::Utf8)
+            .then(|| std::str::from_utf8(self.payload(id)).ok())
+            .flatten()
+    }
+    /// Copies out the precomputed layout, if there is one.
+    pub fn layout(&self) -> Option<Layout> {
+        let start = self.layout?;
+        let f64_at = |at: usize| FP::from_le_bytes(self.bytes[at..at + 8].try_into().unwrap());
+        let coords_at = start + 4;
+        let coords = (0..self.num_nodes)
+            .map(|i| [f64_at(coords_at + 16 * i), f64_at(coords_at + 16 * i + 8)])
+            .collect();
+        let info_at = coords_at + 16 * self.num_nodes;
+        let placed = u32_at(self.bytes, info_at) as usize;
+        let info = (0..placed)
+            .map(|i| {
+                let field = |f: usize| u32_at(self.bytes, info_at + 4 + 20 * i + 4 * f);
+                DFOut {
+                    dagid: field(0) as usize,
+                    depth: field(1) as usize,
+                    generation: field(2) as i32 as isize,
+                    parent_ref: (field(3) != NONE).then(|| (field(3) as usize, field(4) as usize)),
+                }
+            })
+            .collect();
+        Some(Layout {
+            focus: u32_at(self.bytes, start) as usize,
+            coords,
+            info,
+        })
+    }
+    /// Copies the graph into a `DAG`, with each node's data given by `f`.
+    pub fn to_dag<T>(&self, mut f: impl FnMut(DAGID) -> T) -> DAG<T> {
+        let mut dag = DAG::new();
+        for i in 0..self.num_nodes {
+            dag.insert(f(i));
+        }
+        for i in 0..self.num_nodes {
+            self.neighbors(i).for_each(|j| {
+                dag.insert_edge(i, j);
+            });
+        }
+        dag
+    }
+    /// Copies the graph into a [`CsrDag`], with each node's data given by `f`. Cheaper than
+    /// [`BinaryGraph::to_dag`] as the adjacency stays in flat arrays.
+    pub fn to_csr<T>(&self, f: impl FnMut(DAGID) -> T) -> CsrDag<T> {
+        let edges: Vec<_> = (0..self.num_nodes)
+            .flat_map(|i| self.neighbors(i).map(move |j| (i, j)))
+            .collect();
+        CsrDag::from_edges((0..self.num_nodes).map(f).collect(), &edges)
+    }
+}
+
+#[test]
+fn test_binary_round_trip() {
+    let dag = DAG::from_pairs([(10u32, 11), (10, 12), (12, 13), (13, 10)]);
+    let layout = Layout::new(&dag, 0);
+    let mut out = vec![];
+    write(&mut out, &dag, Some(&layout)).unwrap();
+
+    let g = BinaryGraph::new(&out).unwrap();
+    assert_eq!(g.num_nodes(), 4);
+    assert_eq!(g.num_edges(), 4);
+    assert_eq!(g.payload_u32(2), Some(12));
+    assert_eq!(g.to_dag(|i| g.payload_u32(i).unwrap()), dag);
+    assert_eq!(g.to_csr(|i| g.payload_u32(i).unwrap()), CsrDag::from(&dag));
+    assert_eq!(g.layout().as_ref(), Some(&layout));
+
+    let names = dag.map(|v| format!("node {v}"));
+    let mut out = vec![];
+    write(&mut out, &names, None).unwrap();
+    let g = BinaryGraph::new(&out).unwrap();
+    assert_eq!(g.payload_str(3), Some("node 13"));
+    assert_eq!(g.neighbors(0).collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(g.layout(), None);
+
+    assert!(BinaryGraph::new(&out[..out.len() - 4]).is_err());
+    out[4] = VERSION as u8 + 1;
+    assert!(matches!(
+        BinaryGraph::new(&out),
+        Err(Error::Corrupt { offset: 4, .. })
+    ));
+
+    // Sections sized from a huge node count must not overflow.
+    out[4] = VERSION as u8;
+    let mut huge = out.clone();
+    huge[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(BinaryGraph::new(&huge).is_err());
+
+    // Payload offsets start after the 5 edge offsets of 4 nodes, 4 targets and the payload kind.
+    let payload_offsets = 20 + 4 * 5 + 4 * 4 + 4;
+    let mut bad = out.clone();
+    bad[payload_offsets + 4..payload_offsets + 8].copy_from_slice(&1000u32.to_le_bytes());
+    assert!(matches!(
+        BinaryGraph::new(&bad),
+        Err(Error::Corrupt { msg, .. }) if msg == "payload offsets out of order"
+    ));
+    let mut bad = out.clone();
+    bad[payload_offsets + 4 * 5] = 0xff;
+    assert!(matches!(
+        BinaryGraph::new(&bad),
+        Err(Error::Corrupt { msg, .. }) if msg == "invalid Utf8 payload for node 0"
+    ));
+
+    // A u32 payload cut short.
+    let mut out = vec![];
+    write(&mut out, &dag, None).unwrap();
+    out[payload_offsets + 4..payload_offsets + 8].copy_from_slice(&3u32.to_le_bytes());
+    assert!(matches!(
+        BinaryGraph::new(&out),
+        Err(Error::Corrupt { msg, .. }) if msg == "invalid U32 payload for node 0"
+    ));
+
+    let mut out = vec![];
+    write(&mut out, &dag, Some(&Layout { focus: 9, ..layout })).unwrap();
+    assert!(BinaryGraph::new(&out).is_err());
+}
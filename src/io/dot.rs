@@ -0,0 +1,475 @@
+//! GraphViz DOT import and export.
+//!
+//! The reader supports the full statement grammar (subgraphs, edge chains, attribute defaults
+//! and ports), keeping each node's attributes. Edge attributes and graph attributes are parsed
+//! but discarded. Undirected edges are inserted in the order they are written.
+
+use super::Error;
+use crate::dag::{DAG, DAGID};
+use crate::FP;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Display;
+use std::io::{self, Read, Write};
+
+/// A node read from a DOT file.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct DotNode {
+    pub id: String,
+    pub attrs: BTreeMap<String, String>,
+}
+
+impl DotNode {
+    pub fn label(&self) -> Option<&str> {
+        self.attrs.get("label").map(String::as_str)
+    }
+    pub fn color(&self) -> Option<&str> {
+        self.attrs.get("color").map(String::as_str)
+    }
+    pub fn shape(&self) -> Option<&str> {
+        self.attrs.get("shape").map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Id(String),
+    /// A quoted or HTML string, which is never a keyword.
+    Quoted(String),
+    Edge,
+    Sym(char),
+}
+
+fn tokenize(src: &str) -> Result<Vec<(usize, Token)>, Error> {
+    let mut out = vec![];
+    let mut line = 1;
+    let mut chars = src.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '/' if chars.peek() == Some(&'/') => {
+                chars.by_ref().take_while(|&c| c != '\n').for_each(drop);
+                line += 1;
+            }
+            // Preprocessor style lines are treated as comments, like graphviz does.
+            '#' => {
+                chars.by_ref().take_while(|&c| c != '\n').for_each(drop);
+                line += 1;
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                loop {
+                    match chars.next() {
+                        None => return Err(Error::parse(line, "unterminated comment")),
+                        Some('/') if prev == '*' => break,
+                        Some(c) => {
+                            line += (c == '\n') as usize;
+                            prev = c;
+                        }
+                    }
+                }
+            }
+            '-' if matches!(chars.peek(), Some('>' | '-')) => {
+                chars.next();
+                out.push((line, Token::Edge));
+            }
+            '{' | '}' | '[' | ']' | '=' | ';' | ',' | ':' => out.push((line, Token::Sym(c))),
+            '"' => {
+                let start = line;
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        None => return Err(Error::parse(start, "unterminated string")),
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => s.push(c),
+                            // Line continuation.
+                            Some('\n') => line += 1,
+                            Some(c) => s.extend(['\\', c]),
+                            None => return Err(Error::parse(start, "unterminated string")),
+                        },
+                        Some(c) => {
+                            line += (c == '\n') as usize;
+                            s.push(c);
+                        }
+                    }
+                }
+                out.push((start, Token::Quoted(s)));
+            }
+            '<' => {
+                let start = line;
+                let mut depth = 1;
+                let mut s = String::new();
+                while depth > 0 {
+                    let Some(c) = chars.next() else {
+                        return Err(Error::parse(start, "unterminated HTML string"));
+                    };
+                    depth += (c == '<') as i32 - (c == '>') as i32;
+                    line += (c == '\n') as usize;
+                    if depth > 0 {
+                        s.push(c);
+                    }
+                }
+                out.push((start, Token::Quoted(s)));
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let mut s = String::from(c);
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_' || c == '.') {
+                        break;
+                    }
+                    s.push(c);
+                    chars.next();
+                }
+                out.push((line, Token::Id(s)));
+            }
+            c => return Err(Error::parse(line, format!("unexpected character {c:?}"))),
+        }
+    }
+    Ok(out)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    dag: DAG<DotNode>,
+    ids: HashMap<String, DAGID>,
+}
+
+fn is_keyword(t: Option<&Token>, kw: &str) -> bool {
+    matches!(t, Some(Token::Id(s)) if s.eq_ignore_ascii_case(kw))
+}
+
+fn is_id(t: Option<&Token>) -> bool {
+    matches!(t, Some(Token::Id(_) | Token::Quoted(_)))
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |(l, _)| *l)
+    }
+    fn next(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.pos).map(|(_, t)| t.clone());
+        self.pos += 1;
+        t
+    }
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(&Token::Sym(c));
+        self.pos += found as usize;
+        found
+    }
+    fn expect(&mut self, c: char) -> Result<(), Error> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(Error::parse(self.line(), format!("expected {c:?}")))
+        }
+    }
+    fn id(&mut self) -> Result<String, Error> {
+        match self.next() {
+            Some(Token::Id(s) | Token::Quoted(s)) => Ok(s),
+            _ => Err(Error::parse(self.line(), "expected an identifier")),
+        }
+    }
+
+    /// Parses zero or more `[k=v, ...]` blocks.
+    fn attr_list(&mut self) -> Result<Vec<(String, String)>, Error> {
+        let mut attrs = vec![];
+        while self.eat('[') {
+            while !self.eat(']') {
+                let k = self.id()?;
+                self.expect('=')?;
+                attrs.push((k, self.id()?));
+                let _ = self.eat(',') || self.eat(';');
+            }
+        }
+        Ok(attrs)
+    }
+
+    fn node(&mut self, id: String, defaults: &BTreeMap<String, String>) -> DAGID {
+        let dag = &mut self.dag;
+        *self.ids.entry(id).or_insert_with_key(|id| {
+            dag.insert(DotNode {
+                id: id.clone(),
+                attrs: defaults.clone(),
+            })
+        })
+    }
+
+    /// Parses a node id or a subgraph, returning all the nodes it refers to.
+    fn operand(&mut self, defaults: &mut BTreeMap<String, String>) -> Result<Vec<DAGID>, Error> {
+        if is_keyword(self.peek(), "subgraph") || self.peek() == Some(&Token::Sym('{')) {
+            if is_keyword(self.peek(), "subgraph") {
+                self.next();
+                if is_id(self.peek()) {
+                    self.next();
+                }
+            }
+            self.expect('{')?;
+            let mut members = vec![];
+            self.stmt_list(&mut defaults.clone(), &mut members)?;
+            let mut seen = HashSet::new();
+            members.retain(|&n| seen.insert(n));
+            return Ok(members);
+        }
+        let id = self.id()?;
+        // Ports and compass points don't affect the graph structure.
+        while self.eat(':') {
+            self.id()?;
+        }
+        Ok(vec![self.node(id, defaults)])
+    }
+
+    /// Parses statements until the closing `}`, recording every node mentioned in `members`.
+    fn stmt_list(
+        &mut self,
+        defaults: &mut BTreeMap<String, String>,
+        members: &mut Vec<DAGID>,
+    ) -> Result<(), Error> {
+        while !self.eat('}') {
+            if self.peek().is_none() {
+                return Err(Error::parse(self.line(), "expected '}'"));
+            }
+            if is_keyword(self.peek(), "node") {
+                self.next();
+                defaults.extend(self.attr_list()?);
+            } else if is_keyword(self.peek(), "graph") || is_keyword(self.peek(), "edge") {
+                self.next();
+                self.attr_list()?;
+            } else if is_id(self.peek())
+                && self.tokens.get(self.pos + 1).map(|(_, t)| t) == Some(&Token::Sym('='))
+            {
+                self.pos += 2;
+                self.id()?;
+            } else {
+                let mut srcs = self.operand(defaults)?;
+                members.extend(&srcs);
+                if self.peek() == Some(&Token::Edge) {
+                    while self.peek() == Some(&Token::Edge) {
+                        self.next();
+                        let dsts = self.operand(defaults)?;
+                        members.extend(&dsts);
+                        for &src in &srcs {
+                            for &dst in &dsts {
+                                self.dag.insert_edge(src, dst);
+                            }
+                        }
+                        srcs = dsts;
+                    }
+                    self.attr_list()?;
+                } else {
+                    let attrs = self.attr_list()?;
+                    for &n in &srcs {
+                        self.dag.get_mut(n).attrs.extend(attrs.iter().cloned());
+                    }
+                }
+            }
+            self.eat(';');
+        }
+        Ok(())
+    }
+}
+
+/// Reads a single DOT graph.
+pub fn read(mut r: impl Read) -> Result<DAG<DotNode>, Error> {
+    let mut src = String::new();
+    r.read_to_string(&mut src)?;
+    let mut p = Parser {
+        tokens: tokenize(&src)?,
+        pos: 0,
+        dag: DAG::new(),
+        ids: HashMap::new(),
+    };
+    if is_keyword(p.peek(), "strict") {
+        p.next();
+    }
+    if !(is_keyword(p.peek(), "graph") || is_keyword(p.peek(), "digraph")) {
+        return Err(Error::parse(p.line(), "expected `graph` or `digraph`"));
+    }
+    p.next();
+    if is_id(p.peek()) {
+        p.next();
+    }
+    p.expect('{')?;
+    p.stmt_list(&mut BTreeMap::new(), &mut vec![])?;
+    if p.peek().is_some() {
+        return Err(Error::parse(p.line(), "trailing input after graph"));
+    }
+    Ok(p.dag)
+}
+
+/// How a node is written out to DOT.
+pub trait ToDot {
+    fn dot_id(&self) -> String;
+    fn dot_attrs(&self) -> Vec<(String, String)> {
+        vec![]
+    }
+}
+
+impl ToDot for DotNode {
+    fn dot_id(&self) -> String {
+        self.id.clone()
+    }
+    fn dot_attrs(&self) -> Vec<(String, String)> {
+        self.attrs
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+}
+
+impl<T: Display> ToDot for T {
+    fn dot_id(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Number of points per unit of the disc, so that the disc is drawn one inch in radius.
+pub const POS_SCALE: FP = 72.;
+
+/// Quotes an ID. Escapes such as `\n` are kept as written, like the reader does, so only
+/// backslashes which would otherwise escape a quote, another backslash or the closing quote are
+/// doubled.
+fn quote(s: &str) -> String {
+    let mut out = String::from('"');
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' if matches!(chars.peek(), None | Some('"' | '\\')) => out.push_str("\\\\"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Writes a digraph, with each node's position in `positions` written as a pinned `pos`
+/// attribute, i.e. for use with `neato -n`. Node and edge attributes are written as DOT
+/// attributes, after those of the node's payload.
+pub fn write<T: ToDot>(
+    mut w: impl Write,
+    dag: &DAG<T>,
+    positions: Option<&[[FP; 2]]>,
+) -> io::Result<()> {
+    writeln!(w, "digraph {{")?;
+    for i in 0..dag.num_nodes() {
+        let node = dag.get(i);
+        let mut attrs = node.dot_attrs();
+        for (k, col) in dag.node_attrs().columns() {
+            if let Some(v) = col.get(i).filter(|_| attrs.iter().all(|(a, _)| a != k)) {
+                attrs.push((k.to_string(), v.to_string()));
+            }
+        }
+        if let Some(&[x, y]) = positions.and_then(|p| p.get(i)) {
+            attrs.retain(|(k, _)| k != "pos");
+            attrs.push((
+                "pos".into(),
+                format!("{:.3},{:.3}!", x * POS_SCALE, y * POS_SCALE),
+            ));
+        }
+        write!(w, "  {}", quote(&node.dot_id()))?;
+        write_attrs(&mut w, &attrs)?;
+    }
+    for i in 0..dag.num_nodes() {
+        for (&j, &e) in dag.neighbors(i).iter().zip(dag.out_edges(i)) {
+            let (src, dst) = (dag.get(i).dot_id(), dag.get(j).dot_id());
+            write!(w, "  {} -> {}", quote(&src), quote(&dst))?;
+            let attrs: Vec<_> = dag
+                .edge_attrs()
+                .columns()
+                .filter_map(|(k, col)| Some((k.to_string(), col.get(e)?.to_string())))
+                .collect();
+            write_attrs(&mut w, &attrs)?;
+        }
+    }
+    writeln!(w, "}}")
+}
+
+/// Writes an attribute list, if there are any attributes, and ends the statement.
+fn write_attrs(mut w: impl Write, attrs: &[(String, String)]) -> io::Result<()> {
+    if !attrs.is_empty() {
+        let attrs: Vec<_> = attrs
+            .iter()
+            .map(|(k, v)| format!("{}={}", quote(k), quote(v)))
+            .collect();
+        write!(w, " [{}]", attrs.join(", "))?;
+    }
+    writeln!(w, ";")
+}
+
+#[test]
+fn test_read_dot() {
+    let src = r#"
+      /* dependencies */
+      strict digraph deps {
+        rankdir=LR;
+        node [shape=box];
+        core [label="Core lib", color=red];
+        app -> core -> "std lib":port;
+        // the tests depend on everything
+        tests -> { app core }
+        subgraph cluster_x { node [shape=circle] x1 -- x2 }
+      }
+    "#;
+    let dag = read(src.as_bytes()).unwrap();
+    let ids: Vec<_> = (0..dag.num_nodes())
+        .map(|i| dag.get(i).id.as_str())
+        .collect();
+    assert_eq!(ids, ["core", "app", "std lib", "tests", "x1", "x2"]);
+    assert_eq!(dag.get(0).label(), Some("Core lib"));
+    assert_eq!(dag.get(0).color(), Some("red"));
+    assert_eq!(dag.get(0).shape(), Some("box"));
+    assert_eq!(dag.get(4).shape(), Some("circle"));
+    assert_eq!(dag.neighbors(1), &[0]);
+    assert_eq!(dag.neighbors(0), &[2]);
+    assert_eq!(dag.neighbors(3), &[1, 0]);
+    assert_eq!(dag.neighbors(4), &[5]);
+
+    let err = read("digraph { a -> }".as_bytes()).unwrap_err();
+    assert!(matches!(err, Error::Parse { line: 1, .. }), "{err}");
+}
+
+#[test]
+fn test_dot_round_trip() {
+    let src = r#"digraph { a [label="A \"quoted\"\nline"]; a -> b; b -> c; a -> c; "node"
+        c [label="C:\\dir\\"] }"#;
+    let dag = read(src.as_bytes()).unwrap();
+    let positions = [[0., 0.], [0.5, 0.], [0., -0.25]];
+    let mut out = vec![];
+    write(&mut out, &dag, Some(&positions)).unwrap();
+    let round_trip = read(out.as_slice()).unwrap();
+    assert_eq!(round_trip.num_edges(), dag.num_edges());
+    for i in 0..dag.num_nodes() {
+        assert_eq!(round_trip.get(i).id, dag.get(i).id);
+        assert_eq!(round_trip.get(i).label(), dag.get(i).label());
+        assert_eq!(round_trip.neighbors(i), dag.neighbors(i));
+    }
+    assert_eq!(round_trip.get(1).attrs["pos"], "36.000,0.000!");
+    assert_eq!(round_trip.get(0).label(), Some(r#"A "quoted"\nline"#));
+    assert_eq!(round_trip.get(2).label(), Some(r"C:\dir\"));
+    // A quoted keyword is a node.
+    assert_eq!(round_trip.get(3).id, "node");
+
+    use crate::attrs::Value;
+    let mut dag = DAG::from_pairs([("a", "b")]);
+    dag.node_attrs_mut()
+        .set("color", 1, Value::Str("red".into()))
+        .unwrap();
+    dag.edge_attrs_mut()
+        .set("weight", 0, Value::Float(2.5))
+        .unwrap();
+    let mut out = vec![];
+    write(&mut out, &dag, None).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains(r#""b" ["color"="red"];"#), "{out}");
+    assert!(out.contains(r#""a" -> "b" ["weight"="2.5"];"#), "{out}");
+}
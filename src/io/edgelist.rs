@@ -0,0 +1,85 @@
+//! Whitespace separated edge lists, one `src dst [weight]` edge per line.
+//!
+//! Lines starting with `#` or `%` are comments, as are blank lines. Columns past the weight
+//! (e.g. timestamps in KONECT files) are ignored.
+
+use super::Error;
+use crate::dag::DAG;
+use crate::FP;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::{BufRead, BufReader, Read};
+use std::str::FromStr;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeList<T> {
+    pub dag: DAG<T>,
+    /// Weight of each edge in the order they were read, if one was given.
+    pub weights: Vec<Option<FP>>,
+}
+
+/// Reads an edge list, where node names are parsed as `T`, i.e. `String` for arbitrary names or
+/// `u64` for numeric ids.
+pub fn read<T>(r: impl Read) -> Result<EdgeList<T>, Error>
+where
+    T: FromStr + Hash + Eq + Clone,
+    T::Err: std::fmt::Display,
+{
+    let mut ids = HashMap::new();
+    let mut dag = DAG::new();
+    let mut weights = vec![];
+
+    for (i, line) in BufReader::new(r).lines().enumerate() {
+        let line = line?;
+        let line_num = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('%') {
+            continue;
+        }
+        let mut cols = line.split_whitespace();
+        let mut node = |col: Option<&str>| {
+            let col = col.ok_or_else(|| Error::parse(line_num, "expected `src dst [weight]`"))?;
+            let v = col
+                .parse::<T>()
+                .map_err(|e| Error::parse(line_num, format!("invalid node {col:?}: {e}")))?;
+            Ok::<_, Error>(*ids.entry(v).or_insert_with_key(|v| dag.insert(v.clone())))
+        };
+        let src = node(cols.next())?;
+        let dst = node(cols.next())?;
+        let weight = cols
+            .next()
+            .map(|w| {
+                w.parse::<FP>()
+                    .map_err(|e| Error::parse(line_num, format!("invalid weight {w:?}: {e}")))
+            })
+            .transpose()?;
+        dag.insert_edge(src, dst);
+        weights.push(weight);
+    }
+    Ok(EdgeList { dag, weights })
+}
+
+#[test]
+fn test_read_edgelist() {
+    let src = "% konect style header\n# another comment\n\na\tb 1.5\nb c\n\n  c a 2 1234567\n";
+    let EdgeList { dag, weights } = read::<String>(src.as_bytes()).unwrap();
+    assert_eq!(dag.num_nodes(), 3);
+    assert_eq!(dag.num_edges(), 3);
+    assert_eq!(dag.get(0), "a");
+    assert_eq!(dag.neighbors(2), &[0]);
+    assert_eq!(weights, [Some(1.5), None, Some(2.)]);
+
+    let dag = read::<u64>("10 20\n20 30\n".as_bytes()).unwrap().dag;
+    assert_eq!(dag.get(2), &30);
+}
+
+#[test]
+fn test_read_edgelist_errors() {
+    let err = read::<u64>("1 2\n# fine\n3 x\n".as_bytes()).unwrap_err();
+    assert!(matches!(err, Error::Parse { line: 3, .. }), "{err}");
+    let err = read::<String>("a b\nc\n".as_bytes()).unwrap_err();
+    assert!(matches!(err, Error::Parse { line: 2, .. }), "{err}");
+    let err = read::<String>("a b w\n".as_bytes()).unwrap_err();
+    assert!(matches!(err, Error::Parse { line: 1, .. }), "{err}");
+}
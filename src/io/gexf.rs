@@ -0,0 +1,189 @@
+//! GEXF import, as written by Gephi.
+//!
+//! Declared attributes become typed columns named by their `title`. Node labels, edge weights
+//! and labels, and `viz:color` are stored as the `label`, `weight` and `color` columns.
+
+use super::xml::{self, Element};
+use super::Error;
+use crate::attrs::{AttrTable, AttrType, Value};
+use crate::dag::DAG;
+use std::collections::HashMap;
+use std::io::Read;
+
+struct Attribute {
+    title: String,
+    ty: AttrType,
+    default: Option<Value>,
+}
+
+fn attr_type(ty: &str) -> AttrType {
+    match ty {
+        "boolean" => AttrType::Bool,
+        "integer" | "long" | "short" | "byte" => AttrType::Int,
+        "float" | "double" => AttrType::Float,
+        // Lists, dates and URIs are kept as their textual form.
+        _ => AttrType::Str,
+    }
+}
+
+fn attributes<'a>(graph: &'a Element, class: &str) -> Result<HashMap<&'a str, Attribute>, Error> {
+    let mut out = HashMap::new();
+    for group in graph.children_named("attributes") {
+        if group.attr("class") != Some(class) {
+            continue;
+        }
+        for a in group.children_named("attribute") {
+            let ty = attr_type(a.attr("type").unwrap_or("string"));
+            let default = a
+                .children_named("default")
+                .next()
+                .map(|d| Value::parse(ty, &d.text).map_err(|e| Error::parse(d.line, e)))
+                .transpose()?;
+            let id = a.req_attr("id")?;
+            let attr = Attribute {
+                title: a.attr("title").unwrap_or(id).to_string(),
+                ty,
+                default,
+            };
+            out.insert(id, attr);
+        }
+    }
+    Ok(out)
+}
+
+fn set(table: &mut AttrTable, name: &str, row: usize, v: Value, line: usize) -> Result<(), Error> {
+    table.set(name, row, v).map_err(|e| Error::parse(line, e))
+}
+
+fn set_attvalues(
+    el: &Element,
+    row: usize,
+    decls: &HashMap<&str, Attribute>,
+    table: &mut AttrTable,
+) -> Result<(), Error> {
+    for d in decls.values() {
+        table.add_column(&d.title, d.ty);
+        if let Some(default) = &d.default {
+            set(table, &d.title, row, default.clone(), el.line)?;
+        }
+    }
+    for av in el
+        .children_named("attvalues")
+        .flat_map(|avs| avs.children_named("attvalue"))
+    {
+        let id = av.attr("for").or_else(|| av.attr("id"));
+        let Some(d) = id.and_then(|id| decls.get(id)) else {
+            return Err(Error::parse(av.line, "attvalue for undeclared attribute"));
+        };
+        let v = Value::parse(d.ty, av.req_attr("value")?).map_err(|e| Error::parse(av.line, e))?;
+        set(table, &d.title, row, v, av.line)?;
+    }
+    if let Some(label) = el.attr("label") {
+        set(table, "label", row, Value::Str(label.to_string()), el.line)?;
+    }
+    if let Some(c) = el.children_named("color").next() {
+        let [r, g, b] = ["r", "g", "b"].map(|k| c.attr(k).unwrap_or("0"));
+        let color = format!("rgb({r},{g},{b})");
+        set(table, "color", row, Value::Str(color), c.line)?;
+    }
+    Ok(())
+}
+
+/// Collects nodes, including those nested inside hierarchical nodes.
+fn collect_nodes<'a>(el: &'a Element, out: &mut Vec<&'a Element>) {
+    for n in el
+        .children_named("nodes")
+        .flat_map(|ns| ns.children_named("node"))
+    {
+        out.push(n);
+        collect_nodes(n, out);
+    }
+}
+
+/// Reads a GEXF document, with each node's payload being its `id`.
+pub fn read(mut r: impl Read) -> Result<DAG<String>, Error> {
+    let mut src = String::new();
+    r.read_to_string(&mut src)?;
+    let root = xml::parse(&src)?;
+    if root.local_name() != "gexf" {
+        return Err(Error::parse(root.line, "expected <gexf>"));
+    }
+    let Some(graph) = root.children_named("graph").next() else {
+        return Err(Error::parse(root.line, "missing <graph>"));
+    };
+    let node_decls = attributes(graph, "node")?;
+    let edge_decls = attributes(graph, "edge")?;
+
+    let mut out = DAG::new();
+    let mut nodes = vec![];
+    collect_nodes(graph, &mut nodes);
+    let mut ids = HashMap::new();
+    for n in nodes {
+        let id = n.req_attr("id")?;
+        let row = out.insert(id.to_string());
+        if ids.insert(id, row).is_some() {
+            return Err(Error::parse(n.line, format!("duplicate node {id:?}")));
+        }
+        set_attvalues(n, row, &node_decls, out.node_attrs_mut())?;
+    }
+
+    let edges = graph
+        .children_named("edges")
+        .flat_map(|es| es.children_named("edge"));
+    for (row, e) in edges.enumerate() {
+        let endpoint = |name| {
+            let id = e.req_attr(name)?;
+            ids.get(id)
+                .copied()
+                .ok_or_else(|| Error::parse(e.line, format!("unknown node {id:?}")))
+        };
+        out.insert_edge(endpoint("source")?, endpoint("target")?);
+        set_attvalues(e, row, &edge_decls, out.edge_attrs_mut())?;
+        if let Some(w) = e.attr("weight") {
+            let w = Value::parse(AttrType::Float, w).map_err(|err| Error::parse(e.line, err))?;
+            set(out.edge_attrs_mut(), "weight", row, w, e.line)?;
+        }
+    }
+    Ok(out)
+}
+
+#[test]
+fn test_read_gexf() {
+    let src = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gexf xmlns="http://gexf.net/1.3" xmlns:viz="http://gexf.net/1.3/viz" version="1.3">
+  <graph defaultedgetype="directed">
+    <attributes class="node">
+      <attribute id="0" title="year" type="integer"/>
+      <attribute id="1" title="venue" type="string"><default>arXiv</default></attribute>
+    </attributes>
+    <nodes>
+      <node id="a" label="Paper A">
+        <attvalues><attvalue for="0" value="2001"/></attvalues>
+        <viz:color r="255" g="0" b="10"/>
+      </node>
+      <node id="b" label="Paper B"/>
+    </nodes>
+    <edges>
+      <edge id="0" source="b" target="a" weight="2.5"/>
+    </edges>
+  </graph>
+</gexf>"#;
+    let g = read(src.as_bytes()).unwrap();
+    assert_eq!(g.num_nodes(), 2);
+    assert_eq!(g.neighbors(1), &[0]);
+    assert_eq!(g.node_attrs().get("year", 0), Some(Value::Int(2001)));
+    assert_eq!(g.node_attrs().get("year", 1), None);
+    assert_eq!(
+        g.node_attrs().get("venue", 1),
+        Some(Value::Str("arXiv".into()))
+    );
+    assert_eq!(
+        g.node_attrs().get("label", 1),
+        Some(Value::Str("Paper B".into()))
+    );
+    assert_eq!(
+        g.node_attrs().get("color", 0),
+        Some(Value::Str("rgb(255,0,10)".into()))
+    );
+    assert_eq!(g.edge_attrs().floats("weight").unwrap(), [Some(2.5)]);
+}
@@ -0,0 +1,191 @@
+//! GraphML import, as written by yEd, Gephi and networkx.
+//!
+//! Nodes of nested graphs are flattened into one graph. Each `<key>` becomes a typed column
+//! named by its `attr.name`, and `<data>` holding markup rather than text (e.g. yEd's
+//! `nodegraphics`) is skipped.
+
+use super::xml::{self, Element};
+use super::Error;
+use crate::attrs::{AttrTable, AttrType, Value};
+use crate::dag::DAG;
+use std::collections::HashMap;
+use std::io::Read;
+
+struct Key {
+    name: String,
+    ty: AttrType,
+    default: Option<Value>,
+}
+
+fn attr_type(ty: &str, line: usize) -> Result<AttrType, Error> {
+    Ok(match ty {
+        "boolean" => AttrType::Bool,
+        "int" | "long" => AttrType::Int,
+        "float" | "double" => AttrType::Float,
+        "string" => AttrType::Str,
+        t => return Err(Error::parse(line, format!("unknown attr.type {t:?}"))),
+    })
+}
+
+/// Collects all `<node>`s and `<edge>`s of `graph` and its nested graphs.
+fn collect<'a>(graph: &'a Element, nodes: &mut Vec<&'a Element>, edges: &mut Vec<&'a Element>) {
+    for c in &graph.children {
+        match c.local_name() {
+            "node" => nodes.push(c),
+            "edge" => edges.push(c),
+            _ => {}
+        }
+        for g in c.children_named("graph") {
+            collect(g, nodes, edges);
+        }
+    }
+}
+
+fn set_data(
+    el: &Element,
+    row: usize,
+    keys: &HashMap<&str, Key>,
+    table: &mut AttrTable,
+) -> Result<(), Error> {
+    for d in el.children_named("data") {
+        let key = d.req_attr("key")?;
+        let Some(key) = keys.get(key) else {
+            return Err(Error::parse(d.line, format!("undeclared key {key:?}")));
+        };
+        if !d.children.is_empty() {
+            continue;
+        }
+        let v = Value::parse(key.ty, &d.text).map_err(|e| Error::parse(d.line, e))?;
+        table
+            .set(&key.name, row, v)
+            .map_err(|e| Error::parse(d.line, e))?;
+    }
+    Ok(())
+}
+
+fn fill_defaults(keys: &HashMap<&str, Key>, table: &mut AttrTable, len: usize) {
+    table.resize(len);
+    for key in keys.values() {
+        table.add_column(&key.name, key.ty);
+        let Some(default) = &key.default else {
+            continue;
+        };
+        for row in 0..len {
+            if table.get(&key.name, row).is_none() {
+                table.set(&key.name, row, default.clone()).unwrap();
+            }
+        }
+    }
+}
+
+/// Reads a GraphML document, with each node's payload being its `id`.
+pub fn read(mut r: impl Read) -> Result<DAG<String>, Error> {
+    let mut src = String::new();
+    r.read_to_string(&mut src)?;
+    let root = xml::parse(&src)?;
+    if root.local_name() != "graphml" {
+        return Err(Error::parse(root.line, "expected <graphml>"));
+    }
+
+    let mut node_keys = HashMap::new();
+    let mut edge_keys = HashMap::new();
+    for k in root.children_named("key") {
+        let id = k.req_attr("id")?;
+        let ty = attr_type(k.attr("attr.type").unwrap_or("string"), k.line)?;
+        let default = k
+            .children_named("default")
+            .next()
+            .map(|d| Value::parse(ty, &d.text).map_err(|e| Error::parse(d.line, e)))
+            .transpose()?;
+        let key = || Key {
+            name: k.attr("attr.name").unwrap_or(id).to_string(),
+            ty,
+            default: default.clone(),
+        };
+        match k.attr("for").unwrap_or("all") {
+            "node" => drop(node_keys.insert(id, key())),
+            "edge" => drop(edge_keys.insert(id, key())),
+            "all" => {
+                node_keys.insert(id, key());
+                edge_keys.insert(id, key());
+            }
+            _ => {}
+        }
+    }
+
+    let mut nodes = vec![];
+    let mut edges = vec![];
+    for g in root.children_named("graph") {
+        collect(g, &mut nodes, &mut edges);
+    }
+
+    let mut out = DAG::new();
+    let mut ids = HashMap::new();
+    for n in nodes {
+        let id = n.req_attr("id")?;
+        let row = out.insert(id.to_string());
+        if ids.insert(id, row).is_some() {
+            return Err(Error::parse(n.line, format!("duplicate node {id:?}")));
+        }
+        set_data(n, row, &node_keys, out.node_attrs_mut())?;
+    }
+    for (row, e) in edges.into_iter().enumerate() {
+        let endpoint = |name| {
+            let id = e.req_attr(name)?;
+            ids.get(id)
+                .copied()
+                .ok_or_else(|| Error::parse(e.line, format!("unknown node {id:?}")))
+        };
+        out.insert_edge(endpoint("source")?, endpoint("target")?);
+        set_data(e, row, &edge_keys, out.edge_attrs_mut())?;
+    }
+    let (num_nodes, num_edges) = (out.num_nodes(), out.num_edges());
+    fill_defaults(&node_keys, out.node_attrs_mut(), num_nodes);
+    fill_defaults(&edge_keys, out.edge_attrs_mut(), num_edges);
+    Ok(out)
+}
+
+#[test]
+fn test_read_graphml() {
+    let src = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="d0" for="node" attr.name="color" attr.type="string"><default>yellow</default></key>
+  <key id="d1" for="edge" attr.name="weight" attr.type="double"/>
+  <key id="d2" for="node" yfiles.type="nodegraphics"/>
+  <graph id="G" edgedefault="directed">
+    <node id="n0"><data key="d0">green</data></node>
+    <node id="n1"><data key="d2"><y:ShapeNode/></data></node>
+    <edge source="n0" target="n2"><data key="d1">1.5</data></edge>
+    <edge source="n1" target="n0"/>
+    <node id="n2">
+      <graph id="n2:"><node id="n2::n0"/></graph>
+    </node>
+    <edge source="n2" target="n2::n0"/>
+  </graph>
+</graphml>"#;
+    let g = read(src.as_bytes()).unwrap();
+    assert_eq!(g.num_nodes(), 4);
+    assert_eq!(g.get(3), "n2::n0");
+    assert_eq!(g.neighbors(0), &[2]);
+    assert_eq!(g.neighbors(2), &[3]);
+    assert_eq!(
+        g.node_attrs().strs("color").unwrap()[0].as_deref(),
+        Some("green")
+    );
+    assert_eq!(
+        g.node_attrs().strs("color").unwrap()[1].as_deref(),
+        Some("yellow")
+    );
+    assert_eq!(
+        g.edge_attrs().floats("weight").unwrap(),
+        [Some(1.5), None, None]
+    );
+
+    let err = read(
+        r#"<graphml><graph>
+      <edge source="a" target="b"/></graph></graphml>"#
+            .as_bytes(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, Error::Parse { line: 2, .. }), "{err}");
+}
@@ -0,0 +1,275 @@
+//! JSON graph formats used by web tooling: D3/networkx style node-link graphs, and nested
+//! `{name, children}` trees as used by `d3.hierarchy`.
+//!
+//! Keys other than the structural ones are kept as node or edge attributes.
+
+use super::Error;
+use crate::attrs::{AttrTable, Value as AttrValue};
+use crate::dag::{DAG, DAGID};
+use crate::graph::{Direction, Graph};
+use crate::FP;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{self, Read, Write};
+
+fn json_err(e: serde_json::Error) -> Error {
+    if e.is_io() {
+        Error::Io(e.into())
+    } else {
+        Error::parse(e.line(), e.to_string())
+    }
+}
+
+fn to_attr(v: &Value) -> AttrValue {
+    match v {
+        Value::Bool(b) => AttrValue::Bool(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => AttrValue::Int(i),
+            None => AttrValue::Float(n.as_f64().unwrap_or(FP::NAN)),
+        },
+        Value::String(s) => AttrValue::Str(s.clone()),
+        v => AttrValue::Str(v.to_string()),
+    }
+}
+
+/// Stores every key of `obj` except `skip` as attributes of `row`. A column's type is that of
+/// its first value, and later values of a different type are dropped.
+fn set_attrs(table: &mut AttrTable, row: usize, obj: &Map<String, Value>, skip: &[&str]) {
+    for (k, v) in obj.iter().filter(|(k, _)| !skip.contains(&k.as_str())) {
+        let _ = table.set(k, row, to_attr(v));
+    }
+}
+
+/// Textual form of an id, so that `1` and `"1"` refer to the same node.
+fn id_string(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
+
+/// Reads a node-link graph, `{"nodes": [{"id": ..}], "links": [{"source": .., "target": ..}]}`.
+/// Edges may also be under `"edges"`, and endpoints which don't name a node are treated as an
+/// index into `nodes`, as D3 does.
+pub fn read_node_link(r: impl Read) -> Result<DAG<String>, Error> {
+    node_link(&serde_json::from_reader(r).map_err(json_err)?)
+}
+
+fn node_link(v: &Value) -> Result<DAG<String>, Error> {
+    let nodes = v.get("nodes").and_then(Value::as_array);
+    let Some(nodes) = nodes else {
+        return Err(Error::parse(1, "expected a \"nodes\" array"));
+    };
+    let links = v.get("links").or_else(|| v.get("edges"));
+    let links = links
+        .and_then(Value::as_array)
+        .map_or(&[][..], Vec::as_slice);
+
+    let mut out = DAG::new();
+    let mut ids = HashMap::new();
+    for (i, n) in nodes.iter().enumerate() {
+        let empty = Map::new();
+        let obj = n.as_object().unwrap_or(&empty);
+        let id = obj.get("id").map_or_else(|| i.to_string(), id_string);
+        let row = out.insert(id.clone());
+        if ids.insert(id, row).is_some() {
+            return Err(Error::parse(
+                1,
+                format!("duplicate node {:?}", out.get(row)),
+            ));
+        }
+        set_attrs(out.node_attrs_mut(), row, obj, &["id"]);
+    }
+    for (i, l) in links.iter().enumerate() {
+        let Some(obj) = l.as_object() else {
+            return Err(Error::parse(1, format!("link {i} is not an object")));
+        };
+        let endpoint = |key| -> Result<DAGID, Error> {
+            let Some(v) = obj.get(key) else {
+                return Err(Error::parse(1, format!("link {i} missing {key:?}")));
+            };
+            let by_index = v.as_u64().map(|i| i as usize).filter(|&i| i < nodes.len());
+            ids.get(&id_string(v))
+                .copied()
+                .or(by_index)
+                .ok_or_else(|| Error::parse(1, format!("link {i} has unknown {key} {v}")))
+        };
+        let (src, dst) = (endpoint("source")?, endpoint("target")?);
+        out.insert_edge(src, dst);
+        set_attrs(out.edge_attrs_mut(), i, obj, &["source", "target"]);
+    }
+    Ok(out)
+}
+
+/// Writes a node-link graph, with positions written as `x` and `y` on each node.
+pub fn write_node_link<T: Display>(
+    w: impl Write,
+    dag: &DAG<T>,
+    positions: Option<&[[FP; 2]]>,
+) -> io::Result<()> {
+    let nodes: Vec<_> = (0..dag.num_nodes())
+        .map(|i| {
+            let mut node = json!({ "id": dag.get(i).to_string() });
+            if let Some(&[x, y]) = positions.and_then(|p| p.get(i)) {
+                node["x"] = json!(x);
+                node["y"] = json!(y);
+            }
+            node
+        })
+        .collect();
+    let links: Vec<_> = (0..dag.num_nodes())
+        .flat_map(|src| dag.neighbors(src).iter().map(move |&dst| (src, dst)))
+        .map(|(src, dst)| {
+            json!({ "source": dag.get(src).to_string(), "target": dag.get(dst).to_string() })
+        })
+        .collect();
+    let out = json!({ "directed": true, "nodes": nodes, "links": links });
+    serde_json::to_writer(w, &out).map_err(io::Error::from)
+}
+
+/// Reads a nested tree, `{"name": .., "children": [..]}`, with edges from parent to child.
+/// Nodes without a `name` are named by their index.
+pub fn read_tree(r: impl Read) -> Result<DAG<String>, Error> {
+    tree(&serde_json::from_reader(r).map_err(json_err)?)
+}
+
+fn tree(root: &Value) -> Result<DAG<String>, Error> {
+    let mut out = DAG::new();
+    let mut work = vec![(None, root)];
+    while let Some((parent, v)) = work.pop() {
+        let Some(obj) = v.as_object() else {
+            return Err(Error::parse(1, format!("tree node is not an object: {v}")));
+        };
+        let idx = out.num_nodes();
+        let name = obj.get("name").map_or_else(|| idx.to_string(), id_string);
+        let id = out.insert(name);
+        if let Some(parent) = parent {
+            out.insert_edge(parent, id);
+        }
+        set_attrs(out.node_attrs_mut(), id, obj, &["name", "children"]);
+        if let Some(children) = obj.get("children").and_then(Value::as_array) {
+            work.extend(children.iter().rev().map(|c| (Some(id), c)));
+        }
+    }
+    Ok(out)
+}
+
+/// Reads either format, treating documents with a `nodes` list as node-link graphs.
+pub fn read(r: impl Read) -> Result<DAG<String>, Error> {
+    let v: Value = serde_json::from_reader(r).map_err(json_err)?;
+    if v.get("nodes").is_some() {
+        node_link(&v)
+    } else {
+        tree(&v)
+    }
+}
+
+/// Writes the depth-first spanning tree of `dag` from `root` as a nested tree.
+pub fn write_tree<T: Display>(w: impl Write, dag: &DAG<T>, root: DAGID) -> io::Result<()> {
+    let mut children = vec![vec![]; dag.num_nodes()];
+    dag.depth_first_visit(root, Direction::Outgoing, |dfout| {
+        if let Some((parent, _)) = dfout.parent_ref {
+            children[parent].push(dfout.dagid);
+        }
+    });
+    // Written directly with an explicit stack, since both building and serializing a nested
+    // `Value` recurse once per level and overflow on deep trees.
+    let mut w = io::BufWriter::new(w);
+    let mut stack = vec![(root, 0)];
+    while let Some((n, next)) = stack.pop() {
+        if next == 0 {
+            let name = serde_json::to_string(&dag.get(n).to_string())?;
+            write!(w, "{{\"name\":{name}")?;
+            if !children[n].is_empty() {
+                write!(w, ",\"children\":[")?;
+            }
+        }
+        match children[n].get(next) {
+            Some(&c) => {
+                if next > 0 {
+                    write!(w, ",")?;
+                }
+                stack.push((n, next + 1));
+                stack.push((c, 0));
+            }
+            None if next == 0 => write!(w, "}}")?,
+            None => write!(w, "]}}")?,
+        }
+    }
+    w.flush()
+}
+
+/// Each node's name and children, ignoring attributes.
+#[cfg(test)]
+fn structure(g: &DAG<String>) -> Vec<(&str, &[DAGID])> {
+    (0..g.num_nodes())
+        .map(|i| (g.get(i).as_str(), g.neighbors(i)))
+        .collect()
+}
+
+#[test]
+fn test_node_link() {
+    let src = r#"{
+      "directed": true,
+      "nodes": [{"id": "a", "group": 1}, {"id": 7}, {"id": "c", "group": 2.5}],
+      "links": [{"source": "a", "target": 7, "value": 3}, {"source": 2, "target": 0}]
+    }"#;
+    let g = read_node_link(src.as_bytes()).unwrap();
+    assert_eq!(g.get(1), "7");
+    assert_eq!(g.neighbors(0), &[1]);
+    assert_eq!(g.neighbors(2), &[0]);
+    assert_eq!(g.node_attrs().get("group", 0), Some(AttrValue::Int(1)));
+    assert_eq!(g.node_attrs().get("group", 2), None);
+    assert_eq!(g.edge_attrs().get("value", 0), Some(AttrValue::Int(3)));
+
+    let mut out = vec![];
+    write_node_link(&mut out, &g, Some(&[[0., 0.], [0.5, 0.], [0., 0.5]])).unwrap();
+    let round_trip = read_node_link(out.as_slice()).unwrap();
+    assert_eq!(structure(&round_trip), structure(&g));
+    assert_eq!(
+        round_trip.node_attrs().get("x", 1),
+        Some(AttrValue::Float(0.5))
+    );
+
+    let err = read_node_link(r#"{"nodes": [], "links": [{"source": 0}]}"#.as_bytes());
+    assert!(err.is_err());
+    let err = read_node_link(r#"{"nodes": [{"id": "a"}, {"id": "a"}]}"#.as_bytes());
+    assert!(err
+        .unwrap_err()
+        .to_string()
+        .contains(r#"duplicate node "a""#));
+
+    let layout = crate::Layout::new(&g, 0);
+    let s = serde_json::to_string(&layout).unwrap();
+    assert_eq!(serde_json::from_str::<crate::Layout>(&s).unwrap(), layout);
+}
+
+#[test]
+fn test_nested_tree() {
+    let src = r#"{"name": "root", "children": [
+      {"name": "a", "size": 3, "children": [{"name": "a1"}]},
+      {"name": "b"}
+    ]}"#;
+    let g = read_tree(src.as_bytes()).unwrap();
+    let names: Vec<_> = (0..g.num_nodes()).map(|i| g.get(i).as_str()).collect();
+    assert_eq!(names, ["root", "a", "a1", "b"]);
+    assert_eq!(g.neighbors(0), &[1, 3]);
+    assert_eq!(g.node_attrs().get("size", 1), Some(AttrValue::Int(3)));
+
+    let mut out = vec![];
+    write_tree(&mut out, &g, 0).unwrap();
+    assert_eq!(
+        structure(&read_tree(out.as_slice()).unwrap()),
+        structure(&g)
+    );
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"{"name":"root","children":[{"name":"a","children":[{"name":"a1"}]},{"name":"b"}]}"#
+    );
+
+    let deep = DAG::from_pairs((0..100_000).map(|i| (i, i + 1)));
+    let mut out = vec![];
+    write_tree(&mut out, &deep, 0).unwrap();
+    assert!(out.ends_with(&b"]}".repeat(100_000)));
+}
@@ -0,0 +1,126 @@
+//! Readers and writers for graph file formats.
+
+use crate::attrs::Value;
+use crate::dag::DAG;
+use std::fmt;
+use std::fs::File;
+use std::path::Path;
+
+pub mod binary;
+pub mod dot;
+pub mod edgelist;
+pub mod gexf;
+pub mod graphml;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod newick;
+mod xml;
+
+/// Reads a graph file, picking the format from its extension: `.graphml`, `.gexf`, `.dot` or
+/// `.gv`, Newick (`.nwk`, `.newick`, `.tre`, `.nhx`), `.json` when built with `serde`,
+/// `.hypb` binary graphs, and otherwise an edge list. Node payloads are the names used in the file,
+/// and attributes are kept in the graph's node and edge attribute tables. Newick branch lengths
+/// are stored as the `length` node attribute.
+pub fn read_file(path: impl AsRef<Path>) -> Result<DAG<String>, Error> {
+    let path = path.as_ref();
+    let f = File::open(path)?;
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    match ext.to_ascii_lowercase().as_str() {
+        "graphml" => graphml::read(f),
+        "gexf" => gexf::read(f),
+        #[cfg(feature = "serde")]
+        "json" => json::read(f),
+        "hypb" => {
+            let bytes = std::fs::read(path)?;
+            let graph = binary::BinaryGraph::new(&bytes)?;
+            Ok(graph.to_dag(|i| match graph.payload_kind() {
+                // Payloads were checked to decode by `BinaryGraph::new`.
+                binary::PayloadKind::U32 => graph.payload_u32(i).unwrap_or_default().to_string(),
+                binary::PayloadKind::Utf8 => graph.payload_str(i).unwrap_or_default().to_string(),
+                binary::PayloadKind::None => i.to_string(),
+            }))
+        }
+        "nwk" | "newick" | "tre" | "nhx" => {
+            let tree = newick::read(f)?;
+            let mut dag = tree.map(|t| t.name.clone().unwrap_or_default());
+            for i in 0..tree.num_nodes() {
+                let taxon = tree.get(i);
+                let attrs = dag.node_attrs_mut();
+                if let Some(len) = taxon.length {
+                    attrs.set("length", i, Value::Float(len)).unwrap();
+                }
+                for (k, v) in &taxon.nhx {
+                    let _ = attrs.set(k, i, Value::Str(v.clone()));
+                }
+            }
+            Ok(dag)
+        }
+        "dot" | "gv" => {
+            let graph = dot::read(f)?;
+            let mut dag = graph.map(|n| n.id.clone());
+            for i in 0..graph.num_nodes() {
+                for (k, v) in &graph.get(i).attrs {
+                    dag.node_attrs_mut()
+                        .set(k, i, Value::Str(v.clone()))
+                        .unwrap();
+                }
+            }
+            Ok(dag)
+        }
+        _ => {
+            let edges = edgelist::read::<String>(f)?;
+            let mut dag = edges.dag;
+            for (i, w) in edges.weights.into_iter().enumerate() {
+                if let Some(w) = w {
+                    dag.edge_attrs_mut()
+                        .set("weight", i, Value::Float(w))
+                        .unwrap();
+                }
+            }
+            Ok(dag)
+        }
+    }
+}
+
+/// Failure to read a graph, either from the underlying reader or from malformed input.
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    /// Malformed input at a 1-indexed line.
+    Parse {
+        line: usize,
+        msg: String,
+    },
+    /// Malformed binary input at a byte offset.
+    Corrupt {
+        offset: usize,
+        msg: String,
+    },
+}
+
+impl Error {
+    pub(crate) fn parse(line: usize, msg: impl Into<String>) -> Self {
+        Error::Parse {
+            line,
+            msg: msg.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "io error: {e}"),
+            Error::Parse { line, msg } => write!(f, "line {line}: {msg}"),
+            Error::Corrupt { offset, msg } => write!(f, "byte {offset}: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
@@ -0,0 +1,252 @@
+//! Newick phylogenetic trees, including NHX (`[&&NHX:key=value:...]`) annotations.
+//!
+//! Nodes are inserted in pre-order, so the root is always `0`, with edges from parent to child.
+//! Parsing is iterative, so arbitrarily deep (e.g. caterpillar) trees are fine.
+
+use super::Error;
+use crate::dag::{DAG, DAGID};
+use crate::FP;
+use std::collections::BTreeMap;
+use std::io::Read;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Taxon {
+    pub name: Option<String>,
+    /// Length of the branch leading to this node.
+    pub length: Option<FP>,
+    /// NHX annotations, e.g. `S` for species or `B` for bootstrap support.
+    pub nhx: BTreeMap<String, String>,
+}
+
+struct Cursor<'a> {
+    src: &'a [u8],
+    pos: usize,
+    line: usize,
+}
+
+impl Cursor<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+    fn bump(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        self.line += (c == b'\n') as usize;
+        Some(c)
+    }
+    /// Skips whitespace and comments, returning the contents of any NHX comments.
+    fn skip(&mut self) -> Result<Vec<String>, Error> {
+        let mut nhx = vec![];
+        loop {
+            match self.peek() {
+                Some(c) if c.is_ascii_whitespace() => drop(self.bump()),
+                Some(b'[') => {
+                    let line = self.line;
+                    let start = self.pos + 1;
+                    while self.bump() != Some(b']') {
+                        if self.peek().is_none() {
+                            return Err(Error::parse(line, "unterminated comment"));
+                        }
+                    }
+                    let comment = String::from_utf8_lossy(&self.src[start..self.pos - 1]);
+                    if let Some(fields) = comment.strip_prefix("&&NHX") {
+                        nhx.push(fields.to_string());
+                    }
+                }
+                _ => return Ok(nhx),
+            }
+        }
+    }
+    fn label(&mut self) -> Result<Option<String>, Error> {
+        if self.peek() == Some(b'\'') {
+            let line = self.line;
+            self.bump();
+            let mut s = vec![];
+            loop {
+                match self.bump() {
+                    None => return Err(Error::parse(line, "unterminated quoted label")),
+                    Some(b'\'') if self.peek() == Some(b'\'') => {
+                        self.bump();
+                        s.push(b'\'');
+                    }
+                    Some(b'\'') => break,
+                    Some(c) => s.push(c),
+                }
+            }
+            return Ok(Some(String::from_utf8_lossy(&s).into_owned()));
+        }
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_ascii_whitespace() || b"()[]':;,".contains(&c) {
+                break;
+            }
+            self.bump();
+        }
+        let s = String::from_utf8_lossy(&self.src[start..self.pos]);
+        // Unquoted underscores stand for blanks.
+        Ok((!s.is_empty()).then(|| s.replace('_', " ")))
+    }
+}
+
+/// Parses the name, branch length and annotations following a node.
+fn annotate(c: &mut Cursor, taxon: &mut Taxon) -> Result<(), Error> {
+    let mut nhx = c.skip()?;
+    taxon.name = c.label()?;
+    nhx.extend(c.skip()?);
+    if c.peek() == Some(b':') {
+        c.bump();
+        nhx.extend(c.skip()?);
+        let start = c.pos;
+        while c
+            .peek()
+            .is_some_and(|b| b.is_ascii_alphanumeric() || b"+-.".contains(&b))
+        {
+            c.bump();
+        }
+        let len = std::str::from_utf8(&c.src[start..c.pos]).unwrap();
+        let len = len
+            .parse::<FP>()
+            .map_err(|e| Error::parse(c.line, format!("invalid branch length {len:?}: {e}")))?;
+        taxon.length = Some(len);
+    }
+    nhx.extend(c.skip()?);
+    for fields in nhx {
+        for kv in fields.split(':').filter(|kv| !kv.is_empty()) {
+            let (k, v) = kv.split_once('=').unwrap_or((kv, ""));
+            taxon.nhx.insert(k.to_string(), v.to_string());
+        }
+    }
+    Ok(())
+}
+
+fn tree(c: &mut Cursor) -> Result<DAG<Taxon>, Error> {
+    let mut dag = DAG::new();
+    // Nodes whose children are still being read.
+    let mut open: Vec<DAGID> = vec![];
+    loop {
+        c.skip()?;
+        let parent = open.last().copied();
+        let node = dag.insert(Taxon::default());
+        if let Some(parent) = parent {
+            dag.insert_edge(parent, node);
+        }
+        let mut curr = node;
+        if c.peek() == Some(b'(') {
+            c.bump();
+            open.push(node);
+            continue;
+        }
+        // Close any finished internal nodes, then move on to the next sibling.
+        loop {
+            annotate(c, dag.get_mut(curr))?;
+            match c.bump() {
+                Some(b',') if !open.is_empty() => break,
+                Some(b')') if !open.is_empty() => curr = open.pop().unwrap(),
+                Some(b';') if open.is_empty() => return Ok(dag),
+                Some(b) => return Err(Error::parse(c.line, format!("unexpected {:?}", b as char))),
+                None => return Err(Error::parse(c.line, "expected ';'")),
+            }
+        }
+    }
+}
+
+/// Reads every tree in the input.
+pub fn read_all(mut r: impl Read) -> Result<Vec<DAG<Taxon>>, Error> {
+    let mut src = vec![];
+    r.read_to_end(&mut src)?;
+    let mut c = Cursor {
+        src: &src,
+        pos: 0,
+        line: 1,
+    };
+    let mut out = vec![];
+    while {
+        c.skip()?;
+        c.peek().is_some()
+    } {
+        out.push(tree(&mut c)?);
+    }
+    Ok(out)
+}
+
+/// Reads the first tree in the input.
+pub fn read(r: impl Read) -> Result<DAG<Taxon>, Error> {
+    read_all(r)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::parse(1, "no tree found"))
+}
+
+/// Branch lengths indexed by EdgeID, for use as `ProjectOptions::edge_lengths`.
+pub fn branch_lengths(dag: &DAG<Taxon>) -> Vec<FP> {
+    let lengths: Vec<_> = (0..dag.num_nodes()).map(|i| dag.get(i).length).collect();
+    edge_lengths_from_nodes(dag, &lengths)
+}
+
+/// Lengths of each edge, indexed by EdgeID, from those of the branch leading into each node
+/// (where the length of a Newick branch is stored), with missing ones filled in as by
+/// [`fill_missing`].
+pub fn edge_lengths_from_nodes<T>(dag: &DAG<T>, lengths: &[Option<FP>]) -> Vec<FP> {
+    let lengths = fill_missing(lengths);
+    (0..dag.num_edges())
+        .map(|e| lengths[dag.edge(e).1])
+        .collect()
+}
+
+/// Fills in missing lengths with the mean of those given, or `1` if none are.
+pub fn fill_missing(lengths: &[Option<FP>]) -> Vec<FP> {
+    let given: Vec<_> = lengths.iter().flatten().collect();
+    let mean = if given.is_empty() {
+        1.
+    } else {
+        given.iter().copied().sum::<FP>() / given.len() as FP
+    };
+    lengths.iter().map(|l| l.unwrap_or(mean)).collect()
+}
+
+#[test]
+fn test_read_newick() {
+    let src = "((Homo_sapiens:0.1,'Pan''s troglodytes':0.2)Hominini:0.3[&&NHX:S=primates:B=100],\n  Gorilla:1e-1,(,)):0.0;";
+    let dag = read(src.as_bytes()).unwrap();
+    assert_eq!(dag.num_nodes(), 8);
+    let names: Vec<_> = (0..dag.num_nodes())
+        .map(|i| dag.get(i).name.as_deref().unwrap_or(""))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "",
+            "Hominini",
+            "Homo sapiens",
+            "Pan's troglodytes",
+            "Gorilla",
+            "",
+            "",
+            ""
+        ]
+    );
+    assert_eq!(dag.neighbors(0), &[1, 4, 5]);
+    assert_eq!(dag.neighbors(1), &[2, 3]);
+    assert_eq!(dag.get(1).length, Some(0.3));
+    assert_eq!(dag.get(1).nhx["S"], "primates");
+    assert_eq!(dag.get(1).nhx["B"], "100");
+    assert_eq!(dag.get(4).length, Some(0.1));
+
+    let lengths = branch_lengths(&dag);
+    let length = |to: DAGID| lengths[dag.in_edges(to)[0]];
+    assert_eq!(length(2), 0.1);
+    assert_eq!(length(5), length(6));
+
+    assert_eq!(read_all("(a,b);\n(c,d);".as_bytes()).unwrap().len(), 2);
+    let err = read("(a,b;".as_bytes()).unwrap_err();
+    assert!(matches!(err, Error::Parse { line: 1, .. }), "{err}");
+}
+
+#[test]
+fn test_deep_caterpillar() {
+    let depth = 50_000;
+    let src = "(".repeat(depth) + "a" + &",b)".repeat(depth) + ";";
+    let dag = read(src.as_bytes()).unwrap();
+    assert_eq!(dag.num_nodes(), 2 * depth + 1);
+}
@@ -0,0 +1,230 @@
+//! Just enough of an XML parser for the graph exchange formats: elements, attributes, text,
+//! comments, CDATA and the predefined/numeric entities. DTDs are skipped, not validated.
+
+use super::Error;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct Element {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    /// Concatenated text directly inside this element.
+    pub text: String,
+    /// Line of the opening tag.
+    pub line: usize,
+}
+
+impl Element {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+    /// Gets a required attribute, erroring with this element's line.
+    pub fn req_attr(&self, name: &str) -> Result<&str, Error> {
+        self.attr(name)
+            .ok_or_else(|| Error::parse(self.line, format!("<{}> missing {name:?}", self.name)))
+    }
+    /// Children with the given local name, ignoring any namespace prefix.
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |c| c.local_name() == name)
+    }
+    pub fn local_name(&self) -> &str {
+        self.name.rsplit(':').next().unwrap()
+    }
+}
+
+struct Cursor<'a> {
+    src: &'a str,
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+    fn advance(&mut self, n: usize) -> &'a str {
+        let s = &self.src[self.pos..self.pos + n];
+        self.line += s.matches('\n').count();
+        self.pos += n;
+        s
+    }
+    /// Consumes through the next `end`, returning what was before it.
+    fn until(&mut self, end: &str) -> Result<&'a str, Error> {
+        let Some(n) = self.rest().find(end) else {
+            return Err(Error::parse(self.line, format!("expected {end:?}")));
+        };
+        let s = self.advance(n);
+        self.advance(end.len());
+        Ok(s)
+    }
+    fn skip_ws(&mut self) {
+        let n = self.rest().len() - self.rest().trim_start().len();
+        self.advance(n);
+    }
+    fn name(&mut self) -> Result<&'a str, Error> {
+        let n = self
+            .rest()
+            .find(|c: char| c.is_whitespace() || matches!(c, '>' | '/' | '='))
+            .unwrap_or(self.rest().len());
+        if n == 0 {
+            return Err(Error::parse(self.line, "expected a name"));
+        }
+        Ok(self.advance(n))
+    }
+}
+
+fn unescape(s: &str, line: usize) -> Result<String, Error> {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        let Some(end) = rest[i..].find(';') else {
+            return Err(Error::parse(line, "unterminated entity"));
+        };
+        let entity = &rest[i + 1..i + end];
+        let c = match entity {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            e => {
+                let code = if let Some(hex) = e.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()
+                } else {
+                    e.strip_prefix('#').and_then(|d| d.parse().ok())
+                };
+                code.and_then(char::from_u32)
+                    .ok_or_else(|| Error::parse(line, format!("unknown entity &{e};")))?
+            }
+        };
+        out.push(c);
+        rest = &rest[i + end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Skips declarations, comments and processing instructions.
+fn skip_misc(c: &mut Cursor) -> Result<bool, Error> {
+    let rest = c.rest();
+    if rest.starts_with("<?") {
+        c.until("?>")?;
+    } else if rest.starts_with("<!--") {
+        c.until("-->")?;
+    } else if rest.starts_with("<!DOCTYPE") {
+        // Internal subsets may contain `>`, so stop at the first `]>` if there is one.
+        let end = if rest
+            .find('[')
+            .zip(rest.find('>'))
+            .is_some_and(|(b, e)| b < e)
+        {
+            "]>"
+        } else {
+            ">"
+        };
+        c.until(end)?;
+    } else {
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+fn element(c: &mut Cursor) -> Result<Element, Error> {
+    let line = c.line;
+    c.advance(1);
+    let mut el = Element {
+        name: c.name()?.to_string(),
+        line,
+        ..Default::default()
+    };
+    loop {
+        c.skip_ws();
+        if c.rest().starts_with("/>") {
+            c.advance(2);
+            return Ok(el);
+        }
+        if c.rest().starts_with('>') {
+            c.advance(1);
+            break;
+        }
+        let k = c.name()?.to_string();
+        c.skip_ws();
+        if !c.rest().starts_with('=') {
+            return Err(Error::parse(c.line, format!("expected '=' after {k:?}")));
+        }
+        c.advance(1);
+        c.skip_ws();
+        let quote = match c.rest().chars().next() {
+            Some(q @ ('"' | '\'')) => q,
+            _ => return Err(Error::parse(c.line, "expected a quoted attribute value")),
+        };
+        c.advance(1);
+        let v = c.until(if quote == '"' { "\"" } else { "'" })?;
+        el.attrs.push((k, unescape(v, c.line)?));
+    }
+    loop {
+        let text_end = c.rest().find('<').unwrap_or(c.rest().len());
+        let text = c.advance(text_end);
+        el.text.push_str(&unescape(text, c.line)?);
+        if c.rest().is_empty() {
+            return Err(Error::parse(line, format!("unclosed <{}>", el.name)));
+        }
+        if c.rest().starts_with("<![CDATA[") {
+            c.advance(9);
+            el.text.push_str(c.until("]]>")?);
+        } else if c.rest().starts_with("</") {
+            c.advance(2);
+            let name = c.name()?;
+            if name != el.name {
+                return Err(Error::parse(
+                    c.line,
+                    format!("expected </{}>, found </{name}>", el.name),
+                ));
+            }
+            c.until(">")?;
+            return Ok(el);
+        } else if !skip_misc(c)? {
+            el.children.push(element(c)?);
+        }
+    }
+}
+
+/// Parses a document, returning its root element.
+pub(crate) fn parse(src: &str) -> Result<Element, Error> {
+    let mut c = Cursor {
+        src,
+        pos: 0,
+        line: 1,
+    };
+    loop {
+        c.skip_ws();
+        if c.rest().is_empty() {
+            return Err(Error::parse(c.line, "missing root element"));
+        }
+        if !skip_misc(&mut c)? {
+            break;
+        }
+    }
+    if !c.rest().starts_with('<') {
+        return Err(Error::parse(c.line, "expected '<'"));
+    }
+    element(&mut c)
+}
+
+#[test]
+fn test_parse_xml() {
+    let src = "<?xml version=\"1.0\"?>\n<!-- hi -->\n<a x='1' y=\"&lt;2&#x41;\">\n  t&amp;t<b/><![CDATA[<raw>]]>\n  <ns:c>z</ns:c>\n</a>";
+    let a = parse(src).unwrap();
+    assert_eq!(a.attr("y"), Some("<2A"));
+    assert_eq!(a.line, 3);
+    assert_eq!(a.text.trim(), "t&t<raw>");
+    assert_eq!(a.children.len(), 2);
+    assert_eq!(a.children_named("c").next().unwrap().text, "z");
+
+    let err = parse("<a>\n<b></a>").unwrap_err();
+    assert!(matches!(err, Error::Parse { line: 2, .. }), "{err}");
+}
@@ -0,0 +1,175 @@
+use super::dag::{DFOut, EdgeID, DAG, DAGID};
+use super::graph::Graph;
+use super::poincare_ball::PoincarePoint;
+use super::{hyperbolic_project, FP};
+
+/// Source of children for nodes of a [`LazyDag`].
+pub trait Expand {
+    type Node;
+    /// Fetches the children of `node`, e.g. by listing a directory.
+    fn children(&mut self, node: &Self::Node) -> Vec<Self::Node>;
+}
+
+/// A tree which is only fetched from its source where it is needed, so huge hierarchies (such
+/// as a whole filesystem) can be browsed without reading all of them up front.
+pub struct LazyDag<S: Expand> {
+    dag: DAG<S::Node>,
+    expanded: Vec<bool>,
+    source: S,
+}
+
+impl<S: Expand> LazyDag<S> {
+    /// Creates a graph with just `root`, which has DAGID 0.
+    pub fn new(source: S, root: S::Node) -> Self {
+        let mut dag = DAG::new();
+        dag.insert(root);
+        Self {
+            dag,
+            expanded: vec![false],
+            source,
+        }
+    }
+    /// The part of the graph fetched so far.
+    pub fn dag(&self) -> &DAG<S::Node> {
+        &self.dag
+    }
+    pub fn is_expanded(&self, id: DAGID) -> bool {
+        self.expanded[id]
+    }
+    /// Fetches the children of `id`, returning whether any were added, which is never the case
+    /// if it was already expanded.
+    pub fn expand(&mut self, id: DAGID) -> bool {
+        if std::mem::replace(&mut self.expanded[id], true) {
+            return false;
+        }
+        let children = self.source.children(self.dag.get(id));
+        let added = !children.is_empty();
+        for child in children {
+            let child = self.dag.insert(child);
+            self.dag.insert_edge(id, child);
+            self.expanded.push(false);
+        }
+        added
+    }
+    /// Expands every node within `depth` edges below `from`.
+    pub fn expand_depth(&mut self, from: DAGID, depth: usize) {
+        let mut frontier = vec![from];
+        for _ in 0..depth {
+            let mut next = vec![];
+            for id in frontier {
+                self.expand(id);
+                next.extend_from_slice(self.dag.neighbors(id));
+            }
+            frontier = next;
+        }
+    }
+    /// Expands the nodes positioned within hyperbolic distance `radius` of `center`, returning
+    /// whether the graph grew.
+    pub fn expand_near(&mut self, coords: &[[FP; 2]], center: &PoincarePoint, radius: FP) -> bool {
+        let near: Vec<_> = coords
+            .iter()
+            .enumerate()
+            .filter(|&(id, c)| {
+                !self.expanded[id] && PoincarePoint::from_raw(c).dist(center) <= radius
+            })
+            .map(|(id, _)| id)
+            .collect();
+        let mut expanded = false;
+        for id in near {
+            expanded |= self.expand(id);
+        }
+        expanded
+    }
+}
+
+impl<S: Expand> Graph for LazyDag<S> {
+    type Node = S::Node;
+    type Neighbors<'a>
+        = <DAG<S::Node> as Graph>::Neighbors<'a>
+    where
+        S: 'a;
+    fn num_nodes(&self) -> usize {
+        self.dag.num_nodes()
+    }
+    fn neighbors(&self, of: DAGID) -> Self::Neighbors<'_> {
+        Graph::neighbors(&self.dag, of)
+    }
+    fn incoming(&self, to: DAGID) -> Self::Neighbors<'_> {
+        Graph::incoming(&self.dag, to)
+    }
+    fn node(&self, id: DAGID) -> Option<&S::Node> {
+        self.dag.node(id)
+    }
+    fn find_edge(&self, from: DAGID, to: DAGID) -> Option<EdgeID> {
+        self.dag.find_edge(from, to)
+    }
+}
+
+/// Projects a lazy graph from `focus`, expanding nodes which land within `radius` of `center`
+/// (e.g. the point the user has panned to) and re-projecting, for at most `max_rounds` rounds.
+pub fn project_lazy<S: Expand>(
+    g: &mut LazyDag<S>,
+    focus: DAGID,
+    center: &PoincarePoint,
+    radius: FP,
+    max_rounds: usize,
+) -> (Vec<[FP; 2]>, Vec<DFOut>) {
+    let mut rounds = 0;
+    loop {
+        let (coords, info) = hyperbolic_project(g, focus);
+        rounds += 1;
+        if rounds > max_rounds || !g.expand_near(&coords, center, radius) {
+            return (coords, info.collect());
+        }
+    }
+}
+
+/// An infinite binary tree, where node `i` has children `2i + 1` and `2i + 2`.
+#[cfg(test)]
+struct InfiniteBinaryTree;
+
+#[cfg(test)]
+impl Expand for InfiniteBinaryTree {
+    type Node = u64;
+    fn children(&mut self, &node: &u64) -> Vec<u64> {
+        vec![2 * node + 1, 2 * node + 2]
+    }
+}
+
+#[test]
+fn test_lazy_expansion() {
+    let mut g = LazyDag::new(InfiniteBinaryTree, 0);
+    g.expand_depth(0, 3);
+    assert_eq!(g.dag().num_nodes(), 15);
+    assert!(!g.expand(0));
+
+    // Panning towards one leaf only expands that side of the tree.
+    let (coords, _) = hyperbolic_project(&g, 0);
+    let leaf = (7..15).find(|&i| !g.is_expanded(i)).unwrap();
+    let center = PoincarePoint::from_raw(&coords[leaf]);
+    let (coords, _) = project_lazy(&mut g, 0, &center, 1.0, 4);
+    assert_eq!(coords.len(), g.dag().num_nodes());
+    assert!(g.is_expanded(leaf));
+    let expanded_leaves = (7..15).filter(|&i| g.is_expanded(i)).count();
+    assert!(expanded_leaves < 8, "{expanded_leaves}");
+}
+
+#[test]
+fn test_expand_leaf() {
+    struct Leaf;
+    impl Expand for Leaf {
+        type Node = ();
+        fn children(&mut self, _: &()) -> Vec<()> {
+            vec![]
+        }
+    }
+    let mut g = LazyDag::new(Leaf, ());
+    assert!(!g.expand(0));
+    assert!(g.is_expanded(0));
+
+    // So projecting stops after the first round.
+    let mut g = LazyDag::new(Leaf, ());
+    let center = PoincarePoint::from_raw(&[0., 0.]);
+    assert!(!g.expand_near(&[[0., 0.]], &center, 1.));
+    assert!(g.is_expanded(0));
+}
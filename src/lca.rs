@@ -0,0 +1,98 @@
+use super::dag::{DFOut, DAGID};
+use super::graph::{Direction, Graph};
+
+/// Lowest common ancestor queries on a spanning tree of a graph, each answered in O(log n) by
+/// binary lifting.
+#[derive(Debug, Clone)]
+pub struct Lca {
+    /// Depth of each node in the tree, or `None` if it isn't in the tree.
+    depth: Vec<Option<usize>>,
+    /// `up[k][v]` is the `2^k`th ancestor of `v`, or the root if there are fewer.
+    up: Vec<Vec<DAGID>>,
+}
+
+impl Lca {
+    /// Builds from the spanning tree of a traversal, such as the `info` of a
+    /// [`Layout`](crate::Layout).
+    pub fn new(num_nodes: usize, tree: impl IntoIterator<Item = DFOut>) -> Self {
+        let mut depth = vec![None; num_nodes];
+        let mut parent: Vec<_> = (0..num_nodes).collect();
+        for dfout in tree {
+            depth[dfout.dagid] = Some(dfout.depth);
+            if let Some((p, _)) = dfout.parent_ref {
+                parent[dfout.dagid] = p;
+            }
+        }
+        let max_depth = depth.iter().flatten().copied().max().unwrap_or(0);
+        let levels = (usize::BITS - max_depth.leading_zeros()).max(1);
+        let mut up = vec![parent];
+        for k in 1..levels as usize {
+            let prev = &up[k - 1];
+            let next = prev.iter().map(|&a| prev[a]).collect();
+            up.push(next);
+        }
+        Self { depth, up }
+    }
+    /// Builds from the breadth first spanning tree of the descendants of `root`.
+    pub fn from_root(g: &impl Graph, root: DAGID) -> Self {
+        let mut tree = vec![];
+        g.breadth_first_visit(root, Direction::Outgoing, |dfout| tree.push(dfout));
+        Self::new(g.num_nodes(), tree)
+    }
+    /// Depth of `v` in the tree, or `None` if it isn't in the tree.
+    pub fn depth(&self, v: DAGID) -> Option<usize> {
+        self.depth[v]
+    }
+    /// The `k`th ancestor of `v`.
+    fn ancestor(&self, mut v: DAGID, k: usize) -> DAGID {
+        for (i, up) in self.up.iter().enumerate() {
+            if k >> i & 1 == 1 {
+                v = up[v];
+            }
+        }
+        v
+    }
+    /// The deepest node which is an ancestor of both `a` and `b` in the tree, or `None` if either
+    /// isn't in the tree.
+    pub fn lca(&self, a: DAGID, b: DAGID) -> Option<DAGID> {
+        let (da, db) = (self.depth[a]?, self.depth[b]?);
+        let (mut a, mut b) = if da >= db {
+            (self.ancestor(a, da - db), b)
+        } else {
+            (a, self.ancestor(b, db - da))
+        };
+        if a == b {
+            return Some(a);
+        }
+        for up in self.up.iter().rev() {
+            if up[a] != up[b] {
+                a = up[a];
+                b = up[b];
+            }
+        }
+        Some(self.up[0][a])
+    }
+}
+
+#[test]
+fn test_lca() {
+    // Binary tree, with 15 unreachable from the root.
+    let mut pairs: Vec<_> = (1..15).map(|i| ((i - 1) / 2, i)).collect();
+    pairs.push((15, 0));
+    let dag = crate::DAG::from_pairs(pairs);
+    let lca = Lca::from_root(&dag, 0);
+    assert_eq!(lca.lca(7, 8), Some(3));
+    assert_eq!(lca.lca(7, 12), Some(0));
+    assert_eq!(lca.lca(9, 4), Some(4));
+    assert_eq!(lca.lca(11, 11), Some(11));
+    assert_eq!(lca.lca(14, 6), Some(6));
+    assert_eq!(lca.lca(15, 1), None);
+    assert_eq!(lca.depth(14), Some(3));
+
+    // Agrees with walking up parents one at a time on a long path.
+    let path = crate::DAG::from_pairs((0..100).map(|i| (i, i + 1)));
+    let lca = Lca::from_root(&path, 0);
+    for (a, b) in [(3, 97), (64, 63), (100, 0), (50, 50)] {
+        assert_eq!(lca.lca(a, b), Some(a.min(b)));
+    }
+}
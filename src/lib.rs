@@ -1,22 +1,45 @@
-#![allow(incomplete_features)]
-#![feature(adt_const_params, array_chunks)]
+#![feature(array_chunks)]
 // Ignore non upper globals.
 #![allow(non_upper_case_globals)]
 
-use std::hash::Hash;
-
+pub mod aggregate;
+pub mod attrs;
+pub mod centrality;
+pub mod community;
+pub mod csr;
+pub use csr::CsrDag;
 pub mod dag;
-pub use dag::{DFOut, TraversalOrder, DAG, DAGID};
+pub use dag::{DFOut, EdgePolicy, TraversalOrder, DAG, DAGID};
+
+pub mod graph;
+pub use graph::{Direction, Graph};
 
+pub mod embed;
+pub mod generate;
+pub mod io;
+pub mod lazy;
+pub mod lca;
 pub mod map;
+pub mod merge;
 pub mod poincare_ball;
 pub use poincare_ball::PoincarePoint;
+pub mod simplify;
+pub mod stats;
+pub use stats::GraphStats;
+pub mod subgraph;
+pub use subgraph::SubgraphView;
+
+pub mod svg;
+
+#[cfg(test)]
+mod snapshot;
 
 // TODO feature gate this under wasm
 pub mod wasm;
 
 pub type FP = f64;
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct AngleRange {
     /// Starting degree of angle range
@@ -32,24 +55,83 @@ impl AngleRange {
     }
 }
 
+/// A projection of a graph around a focus, as computed by [`hyperbolic_project`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Layout {
+    pub focus: DAGID,
+    /// Location of each node in the Poincare disc, indexed by DAGID.
+    pub coords: Vec<[FP; 2]>,
+    /// Traversal info for each reached node, in the order they were placed.
+    pub info: Vec<DFOut>,
+}
+
+impl Layout {
+    pub fn new(dag: &impl Graph, focus: DAGID) -> Self {
+        let (coords, info) = hyperbolic_project(dag, focus);
+        Self {
+            focus,
+            coords,
+            info: info.collect(),
+        }
+    }
+}
+
+/// Options for [`hyperbolic_project_with`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProjectOptions<'a> {
+    /// Length of each edge, indexed by EdgeID as found by [`Graph::find_edge`], such as the branch
+    /// lengths of a phylogeny. Nodes are then placed at their hyperbolic distance from the focus
+    /// along the tree, rather than spread evenly by depth. Edges the graph doesn't number or
+    /// without a length have unit length, and negative lengths count as 0.
+    pub edge_lengths: Option<&'a [FP]>,
+    /// Which edges are followed out from the focus. Ignored if `split`.
+    pub direction: Direction,
+    /// Lays out the descendants of the focus (reached by outgoing edges) in the upper half of the
+    /// disc and its ancestors (reached by incoming edges) in the lower half, e.g. to separate the
+    /// references of a paper from the papers citing it.
+    pub split: bool,
+}
+
 /// Projects a tree into hyperbolic space, returning the set of coordinates at which each point
 /// is mapped to, as well as the connectivity.
-pub fn hyperbolic_project<T>(
-    dag: &DAG<T>,
+pub fn hyperbolic_project<G: Graph>(
+    dag: &G,
+    focus: DAGID,
+) -> (Vec<[FP; 2]>, impl Iterator<Item = DFOut>) {
+    hyperbolic_project_with(dag, focus, &ProjectOptions::default())
+}
+
+/// [`hyperbolic_project`] with additional options.
+pub fn hyperbolic_project_with<G: Graph>(
+    dag: &G,
     focus: DAGID,
-) -> (Vec<[FP; 2]>, impl Iterator<Item = DFOut>)
-where
-    T: Hash + Eq,
-{
+    opts: &ProjectOptions,
+) -> (Vec<[FP; 2]>, impl Iterator<Item = DFOut>) {
     let mut info: Vec<_> = vec![Default::default(); dag.num_nodes()];
     let mut order = vec![];
-    dag.breadth_first_visit(focus, |dfout| {
-        info[dfout.dagid] = dfout;
-        order.push(dfout.dagid);
-    });
+    let mut placed = vec![false; dag.num_nodes()];
+    let passes: &[Direction] = if opts.split {
+        &[Direction::Outgoing, Direction::Incoming]
+    } else {
+        std::slice::from_ref(&opts.direction)
+    };
+    for &dir in passes {
+        dag.breadth_first_visit(focus, dir, |dfout| {
+            // Only the focus is reached in both passes of a DAG, but cycles may reach others.
+            if !std::mem::replace(&mut placed[dfout.dagid], true) {
+                info[dfout.dagid] = dfout;
+                order.push(dfout.dagid);
+            }
+        });
+    }
 
     let mut angles = vec![AngleRange::default(); dag.num_nodes()];
-    let mut max_depth = 0;
+    // Number of children of each node along incoming and outgoing edges, counted when needed.
+    let mut num_children = vec![[None; 2]; dag.num_nodes()];
+    // Distance from the focus along the tree.
+    let mut dists = vec![0.; dag.num_nodes()];
+    let mut max_dist: FP = 0.;
 
     for &o in &order {
         let v = &info[o];
@@ -62,7 +144,15 @@ where
         }
         let (parent, child_num) = v.parent_ref.unwrap();
 
-        let parent_range = angles[parent];
+        let forwards = v.generation > info[parent].generation;
+        let parent_range = if opts.split && parent == focus {
+            AngleRange {
+                start: if forwards { 0. } else { 180. },
+                size: 180.,
+            }
+        } else {
+            angles[parent]
+        };
         assert!(
             !angles[parent].is_empty(),
             "previous node of Node({:?}) was empty",
@@ -70,25 +160,54 @@ where
         );
 
         let child_num = child_num as FP;
-        let total_segments = 1 + dag.neighbors(parent).len();
+        // Children are numbered among the edges walked in the same direction, except when
+        // walking both ways without splitting, where they are numbered among all edges.
+        let both = opts.direction == Direction::Both && !opts.split;
+        let outgoing = forwards || both;
+        let num_children = *num_children[parent][outgoing as usize].get_or_insert_with(|| {
+            if both {
+                dag.neighbors(parent).count() + dag.incoming(parent).count()
+            } else if outgoing {
+                dag.neighbors(parent).count()
+            } else {
+                dag.incoming(parent).count()
+            }
+        });
+        let total_segments = 1 + num_children;
         assert_ne!(total_segments, 0);
         let segment_size = parent_range.size / (total_segments as FP);
 
         let start = parent_range.start + segment_size * child_num;
         let size = segment_size;
-        max_depth = max_depth.max(v.depth);
         angles[v.dagid] = AngleRange { start, size };
+
+        // The edge walked points to the parent if this was reached by walking it backwards.
+        let edge_len = opts.edge_lengths.map_or(1., |lengths| {
+            let (from, to) = if forwards {
+                (parent, v.dagid)
+            } else {
+                (v.dagid, parent)
+            };
+            let e = dag.find_edge(from, to);
+            e.and_then(|e| lengths.get(e)).copied().unwrap_or(1.)
+        });
+        dists[v.dagid] = dists[parent] + edge_len.max(0.);
+        max_dist = max_dist.max(dists[v.dagid]);
     }
 
     let mut final_locations = vec![[0.; 2]; angles.len()];
     for &o in &order {
         let v = &info[o];
-        let depth = v.depth;
-        if depth == 0 {
+        if v.depth == 0 || max_dist == 0. {
             // root is at center.
             continue;
         }
-        let radius = 0.98 * (depth as FP / max_depth as FP);
+        let radius = if opts.edge_lengths.is_some() {
+            // A point at hyperbolic distance `d` from the origin lies at this radius in the disc.
+            (dists[v.dagid] / 2.).tanh()
+        } else {
+            0.98 * (dists[v.dagid] / max_dist)
+        };
         let angle = angles[v.dagid].start + (angles[v.dagid].size / 2.0);
         let (sin, cos) = angle.to_radians().sin_cos();
         final_locations[v.dagid] = [radius * cos, radius * sin];
@@ -106,24 +225,88 @@ fn test_simple_tree() {
     }
 }
 
+#[test]
+fn test_edge_lengths() {
+    let dag = DAG::from_pairs([(0, 1), (1, 2), (0, 3)]);
+    // 0 -> 1 -> 2, and 0 -> 3.
+    let lengths = [1., 3., 0.5];
+    let opts = ProjectOptions {
+        edge_lengths: Some(&lengths),
+        ..Default::default()
+    };
+    let norm = |[x, y]: [FP; 2]| x.hypot(y);
+    let radius = |d: FP| (d / 2.).tanh();
+    let points = hyperbolic_project_with(&dag, 0, &opts).0;
+    assert!((norm(points[1]) - radius(1.)).abs() < 1e-9);
+    assert!((norm(points[2]) - radius(4.)).abs() < 1e-9);
+    assert!((norm(points[3]) - radius(0.5)).abs() < 1e-9);
+
+    // The same edges are looked up when walking up from the focus.
+    let points = hyperbolic_project_with(&dag, 2, &opts).0;
+    assert!((norm(points[1]) - radius(3.)).abs() < 1e-9);
+
+    // Negative lengths are treated as 0.
+    let lengths = [-1., 3., 0.5];
+    let opts = ProjectOptions {
+        edge_lengths: Some(&lengths),
+        ..Default::default()
+    };
+    assert_eq!(hyperbolic_project_with(&dag, 0, &opts).0[1], [0.; 2]);
+
+    // Only descendants of the focus are placed when following outgoing edges.
+    let opts = ProjectOptions {
+        direction: Direction::Outgoing,
+        ..opts
+    };
+    let (points, info) = hyperbolic_project_with(&dag, 1, &opts);
+    assert_eq!(info.map(|v| v.dagid).collect::<Vec<_>>(), [1, 2]);
+    assert!((norm(points[2]) - radius(3.)).abs() < 1e-9);
+    // Edges past the end of the lengths have unit length.
+    let opts = ProjectOptions {
+        edge_lengths: Some(&[2.]),
+        ..Default::default()
+    };
+    let points = hyperbolic_project_with(&dag, 0, &opts).0;
+    assert!((norm(points[3]) - radius(1.)).abs() < 1e-9);
+}
+
+#[test]
+fn test_split() {
+    // 0 and 1 cite 2, which cites 3 and 4.
+    let mut dag = DAG::new();
+    for i in 0..5 {
+        dag.insert(i);
+    }
+    for (src, dst) in [(0, 2), (1, 2), (2, 3), (2, 4)] {
+        dag.insert_edge(src, dst);
+    }
+    let opts = ProjectOptions {
+        split: true,
+        ..Default::default()
+    };
+    let points = hyperbolic_project_with(&dag, 2, &opts).0;
+    assert_eq!(points[2], [0.; 2]);
+    for descendant in [3, 4] {
+        assert!(points[descendant][1] > 0., "{:?}", points[descendant]);
+    }
+    for ancestor in [0, 1] {
+        assert!(points[ancestor][1] < 0., "{:?}", points[ancestor]);
+    }
+
+    // Without splitting, the parents and children of a node reached from one of its children
+    // share its range.
+    let dag = DAG::from_pairs([(0, 1), (2, 1), (1, 3), (1, 4)]);
+    let points = hyperbolic_project(&dag, 3).0;
+    for i in 0..points.len() {
+        for j in 0..i {
+            assert_ne!(points[i], points[j], "{i} and {j}");
+        }
+    }
+}
+
 #[test]
 fn test_cit_data() {
-    use std::fs::read_to_string;
-    use std::path::Path;
-    let path = Path::new(file!())
-        .parent()
-        .and_then(|p| p.parent())
-        .unwrap()
-        .join("app/cit-DBLP.edges");
-    let data = read_to_string(path).unwrap();
-    let lines: Vec<_> = data
-        .lines()
-        .map(|l| {
-            let mut items = l.split_whitespace().map(|e| e.parse::<u32>().unwrap());
-            let src = items.next().unwrap();
-            (src, items.next().unwrap())
-        })
-        .collect();
-    let dag = DAG::from_pairs(lines);
+    // Comparable in size to cit-DBLP, which has ~12.6k papers and ~50k citations.
+    let dag = generate::citation_dag(12_591, 4, 0);
     let _ = hyperbolic_project(&dag, 0);
 }
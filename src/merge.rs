@@ -0,0 +1,183 @@
+//! Combining and comparing graphs whose nodes are identified by their data, such as two
+//! releases of a dependency graph.
+
+use super::attrs::AttrTable;
+use super::dag::{EdgeError, DAG, DAGID};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// How a node or edge differs between two graphs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Unchanged,
+    Added,
+    Removed,
+}
+
+impl Change {
+    /// SVG colour to draw a node with, or `None` to leave it unchanged.
+    pub fn color(self) -> Option<&'static str> {
+        match self {
+            Change::Unchanged => None,
+            Change::Added => Some("green"),
+            Change::Removed => Some("red"),
+        }
+    }
+}
+
+/// The structural difference between an old and a new graph, from [`DAG::diff`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphDiff<T> {
+    /// Every node and edge of either graph, with the old graph's DAGIDs and EdgeIDs first.
+    pub union: DAG<T>,
+    /// Change of each node of `union`, indexed by DAGID.
+    pub nodes: Vec<Change>,
+    /// Change of each edge of `union`, indexed by EdgeID.
+    pub edges: Vec<Change>,
+}
+
+impl<T> GraphDiff<T> {
+    pub fn added_nodes(&self) -> impl Iterator<Item = DAGID> + '_ {
+        (0..self.nodes.len()).filter(|&v| self.nodes[v] == Change::Added)
+    }
+    pub fn removed_nodes(&self) -> impl Iterator<Item = DAGID> + '_ {
+        (0..self.nodes.len()).filter(|&v| self.nodes[v] == Change::Removed)
+    }
+    /// Colours of the nodes of `union` for [`RenderOptions`](crate::svg::RenderOptions).
+    pub fn node_colors(&self) -> Vec<Option<String>> {
+        self.nodes
+            .iter()
+            .map(|c| c.color().map(str::to_string))
+            .collect()
+    }
+}
+
+/// Copies the attributes of row `from` into row `to`, skipping values of the wrong type.
+fn copy_row(src: &AttrTable, from: usize, dst: &mut AttrTable, to: usize) {
+    for (name, col) in src.columns() {
+        if let Some(v) = col.get(from) {
+            let _ = dst.set(name, to, v);
+        }
+    }
+}
+
+impl<T: Clone + Eq + Hash> DAG<T> {
+    /// Adds the nodes of `other` whose data isn't already in this graph, and the edges of
+    /// `other` which aren't. Attributes of `other` overwrite those of the same node or edge
+    /// here. Returns the DAGID here of each node of `other`.
+    ///
+    /// Fails at the first edge refused by this graph's [`EdgePolicy::Reject`], leaving the nodes
+    /// and the edges before it merged.
+    pub fn merge(&mut self, other: &Self) -> Result<Vec<DAGID>, EdgeError> {
+        let mut ids = HashMap::new();
+        for v in (0..self.num_nodes()).rev() {
+            ids.insert(self.get(v).clone(), v);
+        }
+        let mut mapping = Vec::with_capacity(other.num_nodes());
+        for v in 0..other.num_nodes() {
+            let data = other.get(v);
+            let id = match ids.get(data) {
+                Some(&id) => id,
+                None => {
+                    let id = self.insert(data.clone());
+                    ids.insert(data.clone(), id);
+                    id
+                }
+            };
+            copy_row(other.node_attrs(), v, self.node_attrs_mut(), id);
+            mapping.push(id);
+        }
+        for e in 0..other.num_edges() {
+            let (from, to) = other.edge(e);
+            let (from, to) = (mapping[from], mapping[to]);
+            let id = match self.find_edge(from, to) {
+                Some(id) => Some(id),
+                None => self.try_insert_edge(from, to)?,
+            };
+            if let Some(id) = id {
+                copy_row(other.edge_attrs(), e, self.edge_attrs_mut(), id);
+            }
+        }
+        Ok(mapping)
+    }
+    /// The nodes and edges of both graphs, see [`DAG::merge`].
+    pub fn union(&self, other: &Self) -> Result<Self, EdgeError> {
+        let mut out = self.clone();
+        out.merge(other)?;
+        Ok(out)
+    }
+    /// Which nodes and edges were added and removed going from this graph to `newer`,
+    /// identifying nodes by their data.
+    pub fn diff(&self, newer: &Self) -> Result<GraphDiff<T>, EdgeError> {
+        let mut union = self.clone();
+        let mapping = union.merge(newer)?;
+        let in_newer: HashSet<_> = mapping.iter().copied().collect();
+        let newer_edges: HashSet<_> = (0..newer.num_edges())
+            .map(|e| newer.edge(e))
+            .map(|(from, to)| (mapping[from], mapping[to]))
+            .collect();
+        let change = |old: bool, new: bool| match (old, new) {
+            (true, true) => Change::Unchanged,
+            (false, _) => Change::Added,
+            (true, false) => Change::Removed,
+        };
+        let nodes = (0..union.num_nodes())
+            .map(|v| change(v < self.num_nodes(), in_newer.contains(&v)))
+            .collect();
+        let edges = (0..union.num_edges())
+            .map(|e| change(e < self.num_edges(), newer_edges.contains(&union.edge(e))))
+            .collect();
+        Ok(GraphDiff {
+            union,
+            nodes,
+            edges,
+        })
+    }
+}
+
+#[test]
+fn test_merge() {
+    use crate::attrs::Value;
+    use crate::dag::EdgePolicy;
+    let mut a = DAG::from_pairs([("app", "log"), ("app", "http")]);
+    let mut b = DAG::from_pairs([("app", "http"), ("http", "tls"), ("app", "tls")]);
+    b.node_attrs_mut()
+        .set("version", 1, Value::Str("2.0".into()))
+        .unwrap();
+    let mapping = a.merge(&b).unwrap();
+    assert_eq!(mapping, [0, 2, 3]);
+    assert_eq!(a.num_nodes(), 4);
+    assert_eq!(a.num_edges(), 4);
+    assert_eq!(a.neighbors(0), [1, 2, 3]);
+    assert_eq!(
+        a.node_attrs().get("version", 2),
+        Some(Value::Str("2.0".into()))
+    );
+    // Merging again changes nothing.
+    assert_eq!(a.union(&b).unwrap(), a);
+
+    // Edges refused by the graph merged into are reported.
+    let mut strict = DAG::from_pairs_with([("app", "log")], EdgePolicy::Reject).unwrap();
+    let looped = DAG::from_pairs([("app", "app")]);
+    assert_eq!(strict.merge(&looped), Err(EdgeError::SelfLoop(0)));
+}
+
+#[test]
+fn test_diff() {
+    let old = DAG::from_pairs([("app", "log"), ("app", "http"), ("http", "tls")]);
+    let new = DAG::from_pairs([("app", "http"), ("http", "tls"), ("app", "tracing")]);
+    let diff = old.diff(&new).unwrap();
+    let names = |ids: Vec<DAGID>| ids.iter().map(|&v| *diff.union.get(v)).collect::<Vec<_>>();
+    assert_eq!(names(diff.added_nodes().collect()), ["tracing"]);
+    assert_eq!(names(diff.removed_nodes().collect()), ["log"]);
+    assert_eq!(
+        diff.edges,
+        [
+            Change::Removed,
+            Change::Unchanged,
+            Change::Unchanged,
+            Change::Added
+        ]
+    );
+    assert_eq!(diff.node_colors()[1].as_deref(), Some("red"));
+}
@@ -77,6 +77,23 @@ impl<const N: usize> PoincarePoint<N> {
         (1.0 + 2. * k).acosh()
     }
 
+    /// Samples `n + 1` evenly spaced points along the geodesic from `self` to `o`, including
+    /// both ends.
+    pub fn geodesic(&self, o: &Self, n: usize) -> Vec<Self> {
+        let dir = self.neg().mobius_add(o);
+        let dir_norm = norm(&dir.0);
+        if dir_norm == 0. {
+            return vec![*self; n + 1];
+        }
+        (0..=n)
+            .map(|i| {
+                let t = i as FP / n.max(1) as FP;
+                let k = (t * dir_norm.atanh()).tanh() / dir_norm;
+                self.mobius_add(&Self(kmul(k, &dir.0)))
+            })
+            .collect()
+    }
+
     pub fn is_valid(&self) -> bool {
         norm(&self.0) <= 1.
     }
@@ -118,3 +135,17 @@ fn test_mobius() {
     let zero = PoincarePoint::zero();
     assert_eq!(f, f.mobius_add(&zero));
 }
+
+#[test]
+fn test_geodesic() {
+    let a = PoincarePoint::from_raw(&[0.5, 0.1]);
+    let b = PoincarePoint::from_raw(&[-0.3, 0.6]);
+    let points = a.geodesic(&b, 4);
+    assert_eq!(points.len(), 5);
+    assert_eq!(points[0], a);
+    let total = a.dist(&b);
+    for (i, p) in points.iter().enumerate() {
+        assert!((a.dist(p) - total * i as FP / 4.).abs() < 1e-9);
+        assert!((p.dist(&b) - total * (4 - i) as FP / 4.).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,246 @@
+//! Simplifications of a DAG to the tree structure which is shown when it is projected.
+
+use super::dag::{TraversalOrder, DAG, DAGID};
+use super::graph::Graph;
+
+/// Upper bound on the 64 bit words of reachability kept by [`DAG::transitive_reduction`] at
+/// once, i.e. 32 MiB, unless there are more nodes than that as each node needs at least one.
+const MAX_REACH_WORDS: usize = 1 << 22;
+
+impl<T: Clone> DAG<T> {
+    /// Removes every edge `u -> v` for which there is a longer path from `u` to `v`, and
+    /// duplicate edges, leaving the fewest edges with the same reachability. Returns `None` if
+    /// the graph has a cycle.
+    ///
+    /// Takes `O(E * N / 64)` time. Reachability is tracked for blocks of target nodes at a time,
+    /// using at most 32 MiB, or 8 bytes per node for graphs of over 2^22 nodes.
+    pub fn transitive_reduction(&self) -> Option<Self> {
+        let n = self.num_nodes();
+        let words = n.div_ceil(64).min(MAX_REACH_WORDS / n.max(1)).max(1);
+        self.transitive_reduction_in_blocks(words)
+    }
+
+    /// [`Self::transitive_reduction`], tracking reachability of `64 * words` nodes at a time.
+    fn transitive_reduction_in_blocks(&self, words: usize) -> Option<Self> {
+        let order = self.topological_order()?;
+        let mut position = vec![0; self.num_nodes()];
+        for (i, &v) in order.iter().enumerate() {
+            position[v] = i;
+        }
+        let children: Vec<_> = (0..self.num_nodes())
+            .map(|u| {
+                let mut children = self.neighbors(u).to_vec();
+                // Any child reachable through another is later in topological order.
+                children.sort_by_key(|&v| position[v]);
+                children
+            })
+            .collect();
+        let mut redundant: Vec<_> = children.iter().map(|c| vec![false; c.len()]).collect();
+
+        // Bitset of the nodes in the block reachable from each node, filled in from the sinks up.
+        // An edge `u -> v` is redundant if `v` is reachable from an earlier child of `u`, which
+        // is decided in the block containing `v`.
+        let mut reach = vec![0u64; self.num_nodes() * words];
+        let mut reach_u = vec![0u64; words];
+        for block in (0..self.num_nodes()).step_by(64 * words) {
+            for &u in order.iter().rev() {
+                reach_u.fill(0);
+                for (i, &v) in children[u].iter().enumerate() {
+                    if let Some(b) = v.checked_sub(block).filter(|&b| b < 64 * words) {
+                        if reach_u[b / 64] >> (b % 64) & 1 == 1 {
+                            redundant[u][i] = true;
+                            continue;
+                        }
+                        reach_u[b / 64] |= 1 << (b % 64);
+                    }
+                    for (r, &rv) in reach_u.iter_mut().zip(&reach[v * words..(v + 1) * words]) {
+                        *r |= rv;
+                    }
+                }
+                reach[u * words..(u + 1) * words].copy_from_slice(&reach_u);
+            }
+        }
+        let mut kept: Vec<Vec<_>> = children
+            .iter()
+            .zip(&redundant)
+            .map(|(c, r)| {
+                c.iter()
+                    .zip(r)
+                    .filter(|(_, &r)| !r)
+                    .map(|(&v, _)| v)
+                    .collect()
+            })
+            .collect();
+
+        // Keep surviving edges and their attributes in their original order.
+        let mut out = self.without_edges();
+        let mut kept_ids = vec![];
+        for (u, kept) in kept.iter_mut().enumerate() {
+            for (&v, &e) in self.neighbors(u).iter().zip(self.out_edges(u)) {
+                if let Some(i) = kept.iter().position(|&k| k == v) {
+                    kept.swap_remove(i);
+                    out.insert_edge(u, v);
+                    kept_ids.push(e);
+                }
+            }
+        }
+        *out.edge_attrs_mut() = self.edge_attrs().select(&kept_ids);
+        Some(out)
+    }
+
+    /// The tree where each node's parent is its immediate dominator from `root`: the last node
+    /// other than itself which every path from `root` to it passes through. Nodes unreachable
+    /// from `root` have no edges.
+    pub fn dominator_tree(&self, root: DAGID) -> Self {
+        let idom = dominators(self, root);
+        let mut out = self.without_edges();
+        for (v, &d) in idom.iter().enumerate() {
+            if let Some(d) = d.filter(|_| v != root) {
+                out.insert_edge(d, v);
+            }
+        }
+        out
+    }
+
+    /// Nodes ordered so that every edge points forwards, or `None` if there is a cycle.
+    pub fn topological_order(&self) -> Option<Vec<DAGID>> {
+        let mut in_degree: Vec<_> = (0..self.num_nodes())
+            .map(|v| self.incoming(v).len())
+            .collect();
+        let mut order: Vec<_> = (0..self.num_nodes())
+            .filter(|&v| in_degree[v] == 0)
+            .collect();
+        let mut i = 0;
+        while let Some(&u) = order.get(i) {
+            for &v in self.neighbors(u) {
+                in_degree[v] -= 1;
+                if in_degree[v] == 0 {
+                    order.push(v);
+                }
+            }
+            i += 1;
+        }
+        (order.len() == self.num_nodes()).then_some(order)
+    }
+
+    /// A copy of the nodes and their attributes.
+    fn without_edges(&self) -> Self {
+        let mut out = DAG::with_edge_policy(self.edge_policy());
+        for v in 0..self.num_nodes() {
+            out.insert(self.get(v).clone());
+        }
+        *out.node_attrs_mut() = self.node_attrs().clone();
+        out
+    }
+}
+
+/// The immediate dominator of each node reachable from `root`, with `root` its own, by the
+/// iterative algorithm of Cooper, Harvey and Kennedy.
+pub fn dominators(g: &impl Graph, root: DAGID) -> Vec<Option<DAGID>> {
+    let postorder: Vec<_> = g
+        .depth_first_iter(root, TraversalOrder::PostOrder)
+        .map(|v| v.dagid)
+        .collect();
+    let mut post_num = vec![usize::MAX; g.num_nodes()];
+    for (i, &v) in postorder.iter().enumerate() {
+        post_num[v] = i;
+    }
+
+    let mut idom = vec![None; g.num_nodes()];
+    idom[root] = Some(root);
+    let intersect = |idom: &[Option<DAGID>], mut a: DAGID, mut b: DAGID| {
+        while a != b {
+            while post_num[a] < post_num[b] {
+                a = idom[a].unwrap();
+            }
+            while post_num[b] < post_num[a] {
+                b = idom[b].unwrap();
+            }
+        }
+        a
+    };
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &v in postorder.iter().rev().skip(1) {
+            let new_idom = g
+                .incoming(v)
+                .filter(|&p| idom[p].is_some())
+                .reduce(|a, b| intersect(&idom, a, b));
+            if new_idom.is_some() && new_idom != idom[v] {
+                idom[v] = new_idom;
+                changed = true;
+            }
+        }
+    }
+    idom
+}
+
+#[test]
+fn test_transitive_reduction() {
+    use crate::attrs::Value;
+    // A diamond with a shortcut from top to bottom and a duplicated edge.
+    let mut dag = DAG::from_pairs([
+        (0, 1),
+        (0, 2),
+        (1, 3),
+        (2, 3),
+        (0, 3),
+        (1, 3),
+        (3, 4),
+        (0, 4),
+    ]);
+    for e in 0..dag.num_edges() {
+        dag.edge_attrs_mut()
+            .set("id", e, Value::Int(e as i64))
+            .unwrap();
+    }
+    let reduced = dag.transitive_reduction().unwrap();
+    assert_eq!(reduced.num_nodes(), 5);
+    assert_eq!(reduced.num_edges(), 5);
+    let ids = reduced.edge_attrs().floats("id").unwrap();
+    assert_eq!(ids, [0., 1., 2., 3., 6.].map(Some));
+    assert_eq!(reduced.neighbors(0), [1, 2]);
+    assert_eq!(reduced.neighbors(1), [3]);
+    assert_eq!(reduced.neighbors(3), [4]);
+
+    // Splitting reachability into 5 blocks of 64 nodes gives the same result as a single block.
+    let dag = crate::generate::citation_dag(300, 4, 1);
+    let reduced = dag.transitive_reduction().unwrap();
+    assert!(reduced.num_edges() < dag.num_edges());
+    assert_eq!(dag.transitive_reduction_in_blocks(1).unwrap(), reduced);
+
+    let cycle = DAG::from_pairs([(0, 1), (1, 2), (2, 0)]);
+    assert_eq!(cycle.topological_order(), None);
+    assert_eq!(cycle.transitive_reduction(), None);
+}
+
+#[test]
+fn test_dominator_tree() {
+    // 0 -> 1 -> {2, 3} -> 4 -> 5, 5 -> 1 loops back, and 6 is unreachable.
+    let mut dag = DAG::new();
+    for i in 0..7 {
+        dag.insert(i);
+    }
+    for (u, v) in [
+        (0, 1),
+        (1, 2),
+        (1, 3),
+        (2, 4),
+        (3, 4),
+        (4, 5),
+        (5, 1),
+        (6, 4),
+    ] {
+        dag.insert_edge(u, v);
+    }
+    let idom = dominators(&dag, 0);
+    assert_eq!(
+        idom,
+        [Some(0), Some(0), Some(1), Some(1), Some(1), Some(4), None]
+    );
+    let tree = dag.dominator_tree(0);
+    assert_eq!(tree.neighbors(1), [2, 3, 4]);
+    assert!(tree.incoming(0).is_empty());
+    assert_eq!(tree.num_edges(), 5);
+}
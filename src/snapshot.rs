@@ -0,0 +1,107 @@
+//! Golden-image tests for layouts.
+//!
+//! Each fixture is projected and its rounded coordinates and rendered SVG are compared against
+//! the files in `snapshots/`. Run with `UPDATE_SNAPSHOTS=1` to rewrite the expectations after an
+//! intended layout change.
+
+use super::{hyperbolic_project, svg, DAG, DAGID};
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+fn fixtures() -> Vec<(&'static str, DAG<u32>, DAGID)> {
+    let pairs = |p: &[(u32, u32)]| DAG::from_pairs(p.iter().copied());
+    vec![
+        (
+            "simple_tree",
+            pairs(&[(0, 1), (0, 2), (0, 3), (1, 4), (1, 5), (5, 6), (5, 7)]),
+            0,
+        ),
+        ("path", pairs(&[(0, 1), (1, 2), (2, 3), (3, 4)]), 0),
+        (
+            "path_mid_focus",
+            pairs(&[(0, 1), (1, 2), (2, 3), (3, 4)]),
+            2,
+        ),
+        (
+            "star",
+            pairs(&(1..9).map(|i| (0, i)).collect::<Vec<_>>()),
+            0,
+        ),
+        (
+            "binary_tree",
+            pairs(&(1..15).map(|i| ((i - 1) / 2, i)).collect::<Vec<_>>()),
+            0,
+        ),
+        ("diamond", pairs(&[(0, 1), (0, 2), (1, 3), (2, 3)]), 0),
+        ("cycle", pairs(&[(0, 1), (1, 2), (2, 3), (3, 0)]), 0),
+    ]
+}
+
+fn coords_to_string(coords: &[[f64; 2]]) -> String {
+    // Normalize negative zero so that sign flips of tiny values don't create spurious diffs.
+    let round = |v: f64| {
+        let r = (v * 1e4).round() / 1e4;
+        if r == 0. {
+            0.
+        } else {
+            r
+        }
+    };
+    let mut out = String::new();
+    for (i, &[x, y]) in coords.iter().enumerate() {
+        writeln!(out, "{i} {:.4} {:.4}", round(x), round(y)).unwrap();
+    }
+    out
+}
+
+/// Compares `actual` against the stored snapshot, returning a description of the mismatch.
+fn check(name: &str, actual: &str) -> Result<(), String> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("snapshots")
+        .join(name);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return Ok(());
+    }
+    if !path.exists() {
+        return Err(format!("missing snapshot {name}"));
+    }
+    let expected = fs::read_to_string(&path).unwrap();
+    if expected == actual {
+        return Ok(());
+    }
+    // The side which ended first reads as `<eof>` on the other's next line.
+    let (expected, actual): (Vec<_>, Vec<_>) =
+        (expected.lines().collect(), actual.lines().collect());
+    let line = (0..expected.len().max(actual.len()))
+        .find(|&i| expected.get(i) != actual.get(i))
+        // Only a trailing newline differs.
+        .unwrap_or(expected.len());
+    let e = expected.get(line).copied().unwrap_or("<eof>");
+    let a = actual.get(line).copied().unwrap_or("<eof>");
+    Err(format!(
+        "{name} differs at line {}:\n  expected: {e}\n  actual:   {a}",
+        line + 1
+    ))
+}
+
+#[test]
+fn test_layout_snapshots() {
+    let mut failures = vec![];
+    for (name, dag, focus) in fixtures() {
+        let (coords, info) = hyperbolic_project(&dag, focus);
+        let svg = svg::render(&coords, info, 400.);
+        let results = [
+            check(&format!("{name}.coords"), &coords_to_string(&coords)),
+            check(&format!("{name}.svg"), &svg),
+        ];
+        failures.extend(results.into_iter().filter_map(Result::err));
+    }
+    assert!(
+        failures.is_empty(),
+        "layout snapshots changed (rerun with UPDATE_SNAPSHOTS=1 if intended):\n{}",
+        failures.join("\n")
+    );
+}
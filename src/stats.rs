@@ -0,0 +1,214 @@
+//! Summary metrics of a graph, to judge how it will look before laying it out.
+
+use super::dag::DAGID;
+use super::generate::Rng;
+use super::graph::{Direction, Graph};
+use super::FP;
+use std::fmt;
+
+/// Number of nodes sampled to estimate [`GraphStats::hyperbolicity`].
+const HYPERBOLICITY_SAMPLES: usize = 48;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GraphStats {
+    pub num_nodes: usize,
+    pub num_edges: usize,
+    /// Number of nodes with each in-degree, indexed by degree.
+    pub in_degrees: Vec<usize>,
+    /// Number of nodes with each out-degree, indexed by degree.
+    pub out_degrees: Vec<usize>,
+    /// The node depths are measured from.
+    pub focus: DAGID,
+    /// Number of nodes at each distance from the focus, following edges either way.
+    pub depths: Vec<usize>,
+    /// Mean number of children of the nodes with children in the tree laid out from the focus.
+    pub branching_factor: FP,
+    /// Number of weakly connected components.
+    pub num_components: usize,
+    pub has_cycle: bool,
+    /// Estimate of the Gromov δ-hyperbolicity of the focus's component with edges undirected:
+    /// the largest four-point δ among a sample of its nodes. Trees have δ = 0, and graphs with
+    /// δ small relative to their depth suit a hyperbolic layout.
+    pub hyperbolicity: FP,
+}
+
+/// Counts of each value in `values`, indexed by value.
+fn histogram(values: impl Iterator<Item = usize>) -> Vec<usize> {
+    let mut out = vec![];
+    for v in values {
+        if v >= out.len() {
+            out.resize(v + 1, 0);
+        }
+        out[v] += 1;
+    }
+    out
+}
+
+impl GraphStats {
+    /// Computes all statistics, with depths measured from `focus`.
+    pub fn new<G: Graph>(g: &G, focus: DAGID) -> Self {
+        let nodes: Vec<_> = (0..g.num_nodes())
+            .filter(|&v| g.node(v).is_some())
+            .collect();
+
+        let mut depths = vec![];
+        let mut tree_children = vec![0; g.num_nodes()];
+        let mut component = vec![];
+        g.breadth_first_visit(focus, Direction::Both, |v| {
+            depths.push(v.depth);
+            if let Some((parent, _)) = v.parent_ref {
+                tree_children[parent] += 1;
+            }
+            component.push(v.dagid);
+        });
+        let parents = tree_children.iter().filter(|&&c| c > 0).count();
+
+        let mut seen = vec![false; g.num_nodes()];
+        let mut num_components = 0;
+        for &v in &nodes {
+            if !seen[v] {
+                num_components += 1;
+                g.breadth_first_visit(v, Direction::Both, |u| seen[u.dagid] = true);
+            }
+        }
+
+        Self {
+            num_nodes: nodes.len(),
+            num_edges: nodes.iter().map(|&v| g.neighbors(v).count()).sum(),
+            in_degrees: histogram(nodes.iter().map(|&v| g.incoming(v).count())),
+            out_degrees: histogram(nodes.iter().map(|&v| g.neighbors(v).count())),
+            focus,
+            depths: histogram(depths.into_iter()),
+            branching_factor: if parents == 0 {
+                0.
+            } else {
+                (component.len() - 1) as FP / parents as FP
+            },
+            num_components,
+            has_cycle: has_cycle(g, &nodes),
+            hyperbolicity: hyperbolicity(g, &component),
+        }
+    }
+}
+
+/// Whether following edges forwards from any of `nodes` can return to it.
+fn has_cycle<G: Graph>(g: &G, nodes: &[DAGID]) -> bool {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        New,
+        OnStack,
+        Done,
+    }
+    let mut state = vec![State::New; g.num_nodes()];
+    for &root in nodes {
+        if state[root] != State::New {
+            continue;
+        }
+        state[root] = State::OnStack;
+        let mut stack = vec![(root, g.neighbors(root))];
+        while let Some((v, children)) = stack.last_mut() {
+            match children.next() {
+                Some(c) if state[c] == State::OnStack => return true,
+                Some(c) if state[c] == State::New => {
+                    state[c] = State::OnStack;
+                    stack.push((c, g.neighbors(c)));
+                }
+                Some(_) => {}
+                None => {
+                    state[*v] = State::Done;
+                    stack.pop();
+                }
+            }
+        }
+    }
+    false
+}
+
+/// The largest four-point δ among a random sample of `component`, using undirected distances.
+fn hyperbolicity<G: Graph>(g: &G, component: &[DAGID]) -> FP {
+    let mut sample = component.to_vec();
+    let mut rng = Rng::new(0);
+    // Partial Fisher-Yates shuffle for the first samples.
+    let k = sample.len().min(HYPERBOLICITY_SAMPLES);
+    for i in 0..k {
+        let j = i + rng.below(sample.len() - i);
+        sample.swap(i, j);
+    }
+    sample.truncate(k);
+
+    let mut index = vec![None; g.num_nodes()];
+    for (i, &v) in sample.iter().enumerate() {
+        index[v] = Some(i);
+    }
+    let mut dist = vec![vec![0; k]; k];
+    for (i, &v) in sample.iter().enumerate() {
+        g.breadth_first_visit(v, Direction::Both, |u| {
+            if let Some(j) = index[u.dagid] {
+                dist[i][j] = u.depth;
+            }
+        });
+    }
+
+    let mut delta = 0;
+    for a in 0..k {
+        for b in a + 1..k {
+            for c in b + 1..k {
+                for d in c + 1..k {
+                    let mut sums = [
+                        dist[a][b] + dist[c][d],
+                        dist[a][c] + dist[b][d],
+                        dist[a][d] + dist[b][c],
+                    ];
+                    sums.sort_unstable();
+                    delta = delta.max(sums[2] - sums[1]);
+                }
+            }
+        }
+    }
+    delta as FP / 2.
+}
+
+impl fmt::Display for GraphStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts = |h: &[usize]| {
+            let parts: Vec<_> = (0..h.len())
+                .filter(|&i| h[i] > 0)
+                .map(|i| format!("{i}:{}", h[i]))
+                .collect();
+            parts.join(" ")
+        };
+        writeln!(f, "nodes             {}", self.num_nodes)?;
+        writeln!(f, "edges             {}", self.num_edges)?;
+        writeln!(f, "components        {}", self.num_components)?;
+        writeln!(f, "has cycle         {}", self.has_cycle)?;
+        writeln!(f, "in-degrees        {}", counts(&self.in_degrees))?;
+        writeln!(f, "out-degrees       {}", counts(&self.out_degrees))?;
+        writeln!(f, "depths from {:<6}{}", self.focus, counts(&self.depths))?;
+        writeln!(f, "branching factor  {:.2}", self.branching_factor)?;
+        writeln!(f, "hyperbolicity     {:.1}", self.hyperbolicity)
+    }
+}
+
+#[test]
+fn test_graph_stats() {
+    use crate::DAG;
+    let tree = DAG::from_pairs([(0, 1), (0, 2), (1, 3), (1, 4), (5, 6)]);
+    let stats = GraphStats::new(&tree, 0);
+    assert_eq!(stats.num_nodes, 7);
+    assert_eq!(stats.num_edges, 5);
+    assert_eq!(stats.in_degrees, [2, 5]);
+    assert_eq!(stats.out_degrees, [4, 1, 2]);
+    assert_eq!(stats.depths, [1, 2, 2]);
+    assert_eq!(stats.branching_factor, 2.);
+    assert_eq!(stats.num_components, 2);
+    assert!(!stats.has_cycle);
+    assert_eq!(stats.hyperbolicity, 0.);
+
+    // A 6-cycle has δ = 1: opposite pairs are 3 apart, adjacent pairs 1 or 2.
+    let ring = DAG::from_pairs((0..6).map(|i| (i, (i + 1) % 6)));
+    let stats = GraphStats::new(&ring, 0);
+    assert!(stats.has_cycle);
+    assert_eq!(stats.depths, [1, 2, 2, 1]);
+    assert_eq!(stats.hyperbolicity, 1.);
+}
@@ -0,0 +1,154 @@
+use super::dag::{EdgeID, DAG, DAGID};
+use super::graph::Graph;
+use std::slice;
+
+/// The nodes and edges of a [`DAG`] which match predicates, without copying it.
+///
+/// It is traversed and projected like the graph itself and keeps the graph's DAGIDs, so a
+/// projection of the view is indexed the same way as one of the whole graph.
+pub struct SubgraphView<'a, T, N, E> {
+    dag: &'a DAG<T>,
+    node_filter: N,
+    edge_filter: E,
+}
+
+impl<'a, T, N, E> SubgraphView<'a, T, N, E>
+where
+    N: Fn(DAGID, &T) -> bool,
+    E: Fn(EdgeID) -> bool,
+{
+    /// Keeps the nodes for which `node_filter` holds, and the edges between them for which
+    /// `edge_filter` holds.
+    pub fn new(dag: &'a DAG<T>, node_filter: N, edge_filter: E) -> Self {
+        Self {
+            dag,
+            node_filter,
+            edge_filter,
+        }
+    }
+    pub fn dag(&self) -> &'a DAG<T> {
+        self.dag
+    }
+    pub fn contains_node(&self, id: DAGID) -> bool {
+        id < self.dag.num_nodes() && (self.node_filter)(id, self.dag.get(id))
+    }
+    pub fn contains_edge(&self, id: EdgeID) -> bool {
+        let (from, to) = self.dag.edge(id);
+        (self.edge_filter)(id) && self.contains_node(from) && self.contains_node(to)
+    }
+}
+
+impl<T> DAG<T> {
+    /// A view of the nodes for which `f` holds, and all edges between them.
+    pub fn filter_nodes<N>(&self, f: N) -> SubgraphView<'_, T, N, fn(EdgeID) -> bool>
+    where
+        N: Fn(DAGID, &T) -> bool,
+    {
+        SubgraphView::new(self, f, |_| true)
+    }
+}
+
+/// Neighbors of a node in a [`SubgraphView`].
+pub struct SubgraphNeighbors<'a, T, N, E> {
+    view: &'a SubgraphView<'a, T, N, E>,
+    nodes: slice::Iter<'a, DAGID>,
+    edges: slice::Iter<'a, EdgeID>,
+}
+
+impl<'a, T, N, E> Iterator for SubgraphNeighbors<'a, T, N, E>
+where
+    N: Fn(DAGID, &T) -> bool,
+    E: Fn(EdgeID) -> bool,
+{
+    type Item = DAGID;
+    fn next(&mut self) -> Option<DAGID> {
+        loop {
+            let (&v, &e) = (self.nodes.next()?, self.edges.next()?);
+            if self.view.contains_node(v) && (self.view.edge_filter)(e) {
+                return Some(v);
+            }
+        }
+    }
+}
+
+impl<'v, T, N, E> Graph for SubgraphView<'v, T, N, E>
+where
+    N: Fn(DAGID, &T) -> bool,
+    E: Fn(EdgeID) -> bool,
+{
+    type Node = T;
+    type Neighbors<'a>
+        = SubgraphNeighbors<'a, T, N, E>
+    where
+        Self: 'a;
+    fn num_nodes(&self) -> usize {
+        self.dag.num_nodes()
+    }
+    fn neighbors(&self, of: DAGID) -> Self::Neighbors<'_> {
+        // Nodes outside the view have no edges.
+        let (nodes, edges): (&[_], &[_]) = if self.contains_node(of) {
+            (self.dag.neighbors(of), self.dag.out_edges(of))
+        } else {
+            (&[], &[])
+        };
+        SubgraphNeighbors {
+            view: self,
+            nodes: nodes.iter(),
+            edges: edges.iter(),
+        }
+    }
+    fn incoming(&self, to: DAGID) -> Self::Neighbors<'_> {
+        let (nodes, edges): (&[_], &[_]) = if self.contains_node(to) {
+            (self.dag.incoming(to), self.dag.in_edges(to))
+        } else {
+            (&[], &[])
+        };
+        SubgraphNeighbors {
+            view: self,
+            nodes: nodes.iter(),
+            edges: edges.iter(),
+        }
+    }
+    fn node(&self, id: DAGID) -> Option<&T> {
+        self.contains_node(id).then(|| self.dag.get(id))
+    }
+    fn find_edge(&self, from: DAGID, to: DAGID) -> Option<EdgeID> {
+        let mut edges = self
+            .dag
+            .neighbors(from)
+            .iter()
+            .zip(self.dag.out_edges(from));
+        let (_, &e) = edges.find(|&(&v, &e)| v == to && self.contains_edge(e))?;
+        Some(e)
+    }
+}
+
+#[test]
+fn test_subgraph_projection() {
+    use crate::graph::Direction;
+    // 0 -> {1, 2}, 1 -> 3, 2 -> 4, 3 -> 5.
+    let dag = DAG::from_pairs([(0, 1), (0, 2), (1, 3), (2, 4), (3, 5)]);
+    let view = dag.filter_nodes(|_, &n| n != 2);
+    assert_eq!(view.node(2), None);
+    assert_eq!(view.neighbors(0).collect::<Vec<_>>(), [1]);
+    assert_eq!(view.incoming(4).count(), 0);
+
+    let (coords, info) = crate::hyperbolic_project(&view, 0);
+    let mut placed: Vec<_> = info.map(|v| v.dagid).collect();
+    placed.sort();
+    assert_eq!(placed, [0, 1, 3, 5]);
+    // Laid out like a copy of the remaining path, at the original DAGIDs.
+    let path = crate::hyperbolic_project(&DAG::from_pairs([(0, 1), (1, 3), (3, 5)]), 0).0;
+    assert_eq!(
+        [coords[1], coords[3], coords[5]],
+        [path[1], path[2], path[3]]
+    );
+
+    // Dropping the edge 1 -> 3 cuts off its descendants too.
+    let view = SubgraphView::new(&dag, |_, _| true, |e| dag.edge(e) != (1, 3));
+    let mut reached = vec![];
+    view.breadth_first_visit(0, Direction::Outgoing, |v| reached.push(v.dagid));
+    assert_eq!(reached, [0, 1, 2, 4]);
+    assert_eq!(view.find_edge(1, 3), None);
+    assert_eq!(view.find_edge(2, 4), Some(3));
+}
@@ -0,0 +1,142 @@
+use super::attrs::AttrTable;
+use super::dag::{DFOut, DAGID};
+use super::poincare_ball::PoincarePoint;
+use super::FP;
+use std::fmt::Write;
+
+/// Options for [`render_with`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderOptions<'a> {
+    /// Nodes of a path to draw along geodesics of the disc, such as a
+    /// [`shortest_path`](crate::Graph::shortest_path).
+    pub highlight: &'a [DAGID],
+    /// Text shown when hovering over each node, indexed by DAGID, instead of its DAGID.
+    pub labels: Option<&'a [Option<String>]>,
+    /// SVG colour of each node, indexed by DAGID, instead of blue.
+    pub colors: Option<&'a [Option<String>]>,
+}
+
+impl<'a> RenderOptions<'a> {
+    /// Labels and colours from the `label` and `color` columns of a graph's node attributes.
+    pub fn from_attrs(node_attrs: &'a AttrTable) -> Self {
+        Self {
+            labels: node_attrs.strs("label"),
+            colors: node_attrs.strs("color"),
+            ..Default::default()
+        }
+    }
+}
+
+/// Renders a projected layout into an SVG of the unit disc, `size` pixels wide.
+/// Edges are drawn as straight segments from parent to child.
+pub fn render(coords: &[[FP; 2]], info: impl IntoIterator<Item = DFOut>, size: FP) -> String {
+    render_with(coords, info, size, &RenderOptions::default())
+}
+
+/// [`render`] with additional options.
+pub fn render_with(
+    coords: &[[FP; 2]],
+    info: impl IntoIterator<Item = DFOut>,
+    size: FP,
+    opts: &RenderOptions,
+) -> String {
+    let highlight = opts.highlight;
+    let half = size / 2.;
+    let to_px = |[x, y]: [FP; 2]| [half + half * x, half - half * y];
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {size} {size}">"#
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<circle cx="{half}" cy="{half}" r="{half}" fill="none" stroke="black"/>"#
+    )
+    .unwrap();
+
+    let info: Vec<_> = info.into_iter().collect();
+    for dfout in &info {
+        let Some((parent, _)) = dfout.parent_ref else {
+            continue;
+        };
+        let [x1, y1] = to_px(coords[parent]);
+        let [x2, y2] = to_px(coords[dfout.dagid]);
+        writeln!(
+            out,
+            r#"<line x1="{x1:.3}" y1="{y1:.3}" x2="{x2:.3}" y2="{y2:.3}" stroke="gray"/>"#
+        )
+        .unwrap();
+    }
+    if highlight.len() > 1 {
+        let mut points = String::new();
+        for (i, w) in highlight.windows(2).enumerate() {
+            let [a, b] = [w[0], w[1]].map(|n| PoincarePoint::from_raw(&coords[n]));
+            // Consecutive arcs share an endpoint.
+            for p in a.geodesic(&b, 16).into_iter().skip((i > 0) as usize) {
+                let [x, y] = to_px(p.0);
+                write!(points, "{x:.3},{y:.3} ").unwrap();
+            }
+        }
+        writeln!(
+            out,
+            r#"<polyline points="{}" fill="none" stroke="red" stroke-width="2"/>"#,
+            points.trim_end()
+        )
+        .unwrap();
+    }
+    for dfout in &info {
+        let [cx, cy] = to_px(coords[dfout.dagid]);
+        let i = dfout.dagid;
+        let color = opts.colors.and_then(|c| c.get(i)?.as_deref());
+        let id = i.to_string();
+        let label = opts.labels.and_then(|l| l.get(i)?.as_deref());
+        let (color, label) = (color.unwrap_or("blue"), label.unwrap_or(&id));
+        writeln!(
+            out,
+            r#"<circle cx="{cx:.3}" cy="{cy:.3}" r="2" fill="{}"><title>{}</title></circle>"#,
+            escape(color),
+            escape(label),
+        )
+        .unwrap();
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Escapes text for use in SVG attributes and content.
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[test]
+fn test_render_attrs() {
+    use crate::attrs::Value;
+    let mut attrs = AttrTable::new();
+    attrs.set("label", 1, Value::Str("a & b".into())).unwrap();
+    attrs.set("color", 0, Value::Str("red".into())).unwrap();
+    let info = [
+        DFOut::root(0),
+        DFOut {
+            dagid: 1,
+            depth: 1,
+            generation: 1,
+            parent_ref: Some((0, 0)),
+        },
+    ];
+    let svg = render_with(
+        &[[0.; 2], [0.5, 0.]],
+        info,
+        100.,
+        &RenderOptions::from_attrs(&attrs),
+    );
+    assert!(svg.contains(r#"fill="red"><title>0</title>"#), "{svg}");
+    assert!(
+        svg.contains(r#"fill="blue"><title>a &amp; b</title>"#),
+        "{svg}"
+    );
+}
@@ -1,15 +1,29 @@
+use super::centrality;
+use super::csr::CsrDag;
 use super::dag::{DFOut, DAG};
+use super::embed;
+use super::io::binary::BinaryGraph;
+use super::io::{self, edgelist, gexf, graphml, newick};
 use super::map::{self, Mapping};
 use super::poincare_ball::PoincarePoint;
 use wasm_bindgen::prelude::*;
 
-use super::FP;
+use super::{Direction, Graph, GraphStats, Layout, ProjectOptions, FP};
 
 #[wasm_bindgen]
 pub struct DAGVisualizer {
-    dag: DAG<u32>,
+    dag: CsrDag<u32>,
 
     info: Vec<DFOut>,
+
+    /// Length of each edge, indexed by EdgeID, if the input had any.
+    edge_lengths: Option<Vec<FP>>,
+
+    /// Layout loaded along with the graph, used instead of projecting from its focus.
+    precomputed: Option<Layout>,
+
+    /// Whether ancestors and descendants of the focus are laid out in separate halves.
+    split: bool,
 }
 
 #[wasm_bindgen]
@@ -17,17 +31,118 @@ impl DAGVisualizer {
     #[wasm_bindgen(constructor)]
     pub fn new(src: &[u32], dst: &[u32]) -> DAGVisualizer {
         let data = src.iter().cloned().zip(dst.iter().cloned());
-        let dag = DAG::from_pairs(data);
-        Self { dag, info: vec![] }
+        Self::from_dag(DAG::from_pairs(data))
+    }
+
+    /// Parses a whitespace separated edge list, as in `io::edgelist`.
+    pub fn from_edgelist(text: &str) -> Result<DAGVisualizer, JsValue> {
+        let edges = edgelist::read::<u32>(text.as_bytes()).map_err(to_js)?;
+        Ok(Self::from_dag(edges.dag))
+    }
+
+    /// Parses a GraphML document.
+    pub fn from_graphml(text: &str) -> Result<DAGVisualizer, JsValue> {
+        let graph = graphml::read(text.as_bytes()).map_err(to_js)?;
+        Ok(Self::from_attr_graph(graph))
+    }
+
+    /// Parses a GEXF document.
+    pub fn from_gexf(text: &str) -> Result<DAGVisualizer, JsValue> {
+        let graph = gexf::read(text.as_bytes()).map_err(to_js)?;
+        Ok(Self::from_attr_graph(graph))
+    }
+
+    /// Parses the first tree of a Newick file, using its branch lengths as edge lengths.
+    pub fn from_newick(text: &str) -> Result<DAGVisualizer, JsValue> {
+        let tree = newick::read(text.as_bytes()).map_err(to_js)?;
+        let edge_lengths = newick::branch_lengths(&tree);
+        let mut next_id = 0..;
+        let mut out = Self::from_dag(tree.map(|_| next_id.next().unwrap()));
+        out.edge_lengths = Some(edge_lengths);
+        Ok(out)
+    }
+
+    /// Loads a graph in the binary format of `io::binary`, along with its layout if it has one,
+    /// without parsing it or projecting at the stored focus.
+    pub fn from_binary(bytes: &[u8]) -> Result<DAGVisualizer, JsValue> {
+        let graph = BinaryGraph::new(bytes).map_err(to_js)?;
+        let mut out = Self::from_csr(graph.to_csr(|i| i as u32));
+        out.precomputed = graph.layout();
+        Ok(out)
+    }
+
+    /// The focus of the precomputed layout, if there is one.
+    pub fn precomputed_focus(&self) -> Option<usize> {
+        self.precomputed.as_ref().map(|l| l.focus)
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.dag.num_nodes()
     }
 
     /// Returns a flattened vector of Vec<[f64;2]> coordinates of tree elements
     pub fn coordinates(&mut self, focus: usize) -> Vec<f64> {
-        let (coords, info) = super::hyperbolic_project(&self.dag, focus);
+        let layout = self.layout(focus);
+        self.info = layout.info;
+        layout.coords.into_iter().flatten().collect()
+    }
+
+    /// Like `coordinates`, but placing nodes so that hyperbolic distances match distances in
+    /// the graph, see `embed::hyperbolic_embed`.
+    pub fn embedded_coordinates(&mut self, focus: usize) -> Vec<f64> {
+        let (coords, info) = embed::hyperbolic_embed(&self.dag, focus, &Default::default());
         self.info = info.collect();
         coords.into_iter().flatten().collect()
     }
 
+    /// Lays out ancestors of the focus in the lower half of the disc and descendants in the
+    /// upper half from the next call to `coordinates`.
+    pub fn set_split(&mut self, split: bool) {
+        self.split = split;
+    }
+
+    /// Signed generation of each node from the last focus, negative for ancestors.
+    pub fn generations(&self) -> Vec<i32> {
+        let mut out = vec![0; self.dag.num_nodes()];
+        for dfout in &self.info {
+            out[dfout.dagid] = dfout.generation as i32;
+        }
+        out
+    }
+
+    /// Nodes along a shortest path from `from` to `to`, following edges either way unless
+    /// `directed`. Empty if there is no path.
+    pub fn shortest_path(&self, from: usize, to: usize, directed: bool) -> Vec<usize> {
+        let dir = if directed {
+            Direction::Outgoing
+        } else {
+            Direction::Both
+        };
+        self.dag.shortest_path(from, to, dir).unwrap_or_default()
+    }
+
+    /// A central node to focus on in the initial view, see `centrality::suggest_focus`.
+    pub fn suggest_focus(&self) -> usize {
+        centrality::suggest_focus(&self.dag).unwrap_or(0)
+    }
+
+    /// PageRank of each node along outgoing edges.
+    pub fn pagerank(&self) -> Vec<f64> {
+        centrality::pagerank(&self.dag, 0.85, 50)
+    }
+
+    /// Betweenness of each node with edges undirected, estimated from `samples` random sources,
+    /// or exact if `samples` is 0.
+    pub fn betweenness(&self, samples: usize) -> Vec<f64> {
+        let samples = (samples > 0).then_some(samples);
+        centrality::betweenness(&self.dag, Direction::Both, samples)
+    }
+
+    /// A plain text report of the graph's statistics, with depths measured from `focus`.
+    pub fn stats(&self, focus: usize) -> String {
+        GraphStats::new(&self.dag, focus).to_string()
+    }
+
     /// Returns a flattened vector of `parent` -> `child` connections.
     pub fn connectivity(&self) -> Vec<usize> {
         self.info
@@ -38,6 +153,74 @@ impl DAGVisualizer {
     }
 }
 
+#[cfg(feature = "serde")]
+#[wasm_bindgen]
+impl DAGVisualizer {
+    /// Parses a D3 style node-link JSON graph.
+    pub fn from_node_link(text: &str) -> Result<DAGVisualizer, JsValue> {
+        let graph = io::json::read_node_link(text.as_bytes()).map_err(to_js)?;
+        Ok(Self::from_attr_graph(graph))
+    }
+
+    /// Parses a nested `{name, children}` JSON tree.
+    pub fn from_tree_json(text: &str) -> Result<DAGVisualizer, JsValue> {
+        let graph = io::json::read_tree(text.as_bytes()).map_err(to_js)?;
+        Ok(Self::from_attr_graph(graph))
+    }
+
+    /// Returns the layout drawn by `coordinates` as JSON.
+    pub fn layout_json(&self, focus: usize) -> String {
+        serde_json::to_string(&self.layout(focus)).unwrap()
+    }
+
+    /// Returns the graph's statistics as JSON, with depths measured from `focus`.
+    pub fn stats_json(&self, focus: usize) -> String {
+        serde_json::to_string(&GraphStats::new(&self.dag, focus)).unwrap()
+    }
+}
+
+impl DAGVisualizer {
+    /// The precomputed layout if it has this focus, or else a projection with the current
+    /// options.
+    fn layout(&self, focus: usize) -> Layout {
+        let precomputed = self.precomputed.as_ref().filter(|_| !self.split);
+        if let Some(layout) = precomputed.filter(|l| l.focus == focus) {
+            return layout.clone();
+        }
+        let opts = ProjectOptions {
+            edge_lengths: self.edge_lengths.as_deref(),
+            split: self.split,
+            ..Default::default()
+        };
+        let (coords, info) = super::hyperbolic_project_with(&self.dag, focus, &opts);
+        Layout {
+            focus,
+            coords,
+            info: info.collect(),
+        }
+    }
+    fn from_dag(dag: DAG<u32>) -> Self {
+        Self::from_csr(CsrDag::from(&dag))
+    }
+    fn from_csr(dag: CsrDag<u32>) -> Self {
+        Self {
+            dag,
+            info: vec![],
+            edge_lengths: None,
+            precomputed: None,
+            split: false,
+        }
+    }
+    fn from_attr_graph(graph: DAG<String>) -> Self {
+        let mut next_id = 0..;
+        Self::from_dag(graph.map(|_| next_id.next().unwrap()))
+    }
+}
+
+fn to_js(e: io::Error) -> JsValue {
+    JsValue::from_str(&e.to_string())
+}
+
 #[wasm_bindgen]
 pub struct Maps;
 
@@ -58,6 +241,16 @@ impl Maps {
             .collect()
     }
 
+    /// Flattened points along the geodesics between consecutive nodes of `path`, placed at the
+    /// flattened `coords`, with `samples` segments per edge.
+    pub fn geodesics(coords: &[FP], path: &[usize], samples: usize) -> Vec<FP> {
+        let point = |n: usize| PoincarePoint::from_raw(&[coords[2 * n], coords[2 * n + 1]]);
+        path.windows(2)
+            .flat_map(|w| point(w[0]).geodesic(&point(w[1]), samples))
+            .flat_map(|p| p.0)
+            .collect()
+    }
+
     pub fn shift(uvs: &[FP], x: FP, y: FP, r: FP) -> Vec<FP> {
         let shift = PoincarePoint::exp(&[x, y]);
         uvs.array_chunks::<2>()